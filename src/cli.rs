@@ -0,0 +1,100 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+
+// Parses a command argument, panicking with the argument's name if invalid
+fn arg<T>(value: &str, name: &str) -> T
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    value
+        .parse()
+        .unwrap_or_else(|e| panic!("Invalid {} {:?}: {:?}", name, value, e))
+}
+
+// Parses a comma separated list of command arguments
+fn list<T>(value: &str, name: &str) -> Vec<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    value.split(',').map(|v| arg(v, name)).collect()
+}
+
+/**
+ * Runs an extra command for a day, e.g. `cargo run -- 1 top 5` or
+ * `cargo run -- 1 stream 3 < calories.txt`. Returns false
+ * if the command isn't recognised.
+ */
+pub fn run(args: &[String]) -> bool {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args[..] {
+        ["1", "top", n] => crate::puzzles::day1::top(arg(n, "N")),
+        ["1", "stream", n] => crate::puzzles::day1::stream(arg(n, "N"), None),
+        ["1", "stream", n, path] => crate::puzzles::day1::stream(arg(n, "N"), Some(path)),
+        ["1", "stats"] => crate::puzzles::day1::stats("table", 10),
+        ["1", "stats", format] => crate::puzzles::day1::stats(format, 10),
+        ["1", "stats", format, buckets] => {
+            crate::puzzles::day1::stats(format, arg(buckets, "bucket count"))
+        }
+        ["2", "analysis"] => crate::puzzles::day2::analysis("rps"),
+        ["2", "analysis", game] => crate::puzzles::day2::analysis(game),
+        ["2", "decode", opponent, player, outcome] => {
+            crate::puzzles::day2::decode("rps", opponent, player, outcome)
+        }
+        ["2", "decode", opponent, player, outcome, game] => {
+            crate::puzzles::day2::decode(game, opponent, player, outcome)
+        }
+        ["3", "diagnostics"] => crate::puzzles::day3::diagnostics("lowest"),
+        ["3", "diagnostics", tie_break] => crate::puzzles::day3::diagnostics(tie_break),
+        ["6", "stream", sizes] => {
+            crate::puzzles::day6::stream(&list(sizes, "window size"), None, "chars")
+        }
+        ["6", "stream", sizes, path] => {
+            crate::puzzles::day6::stream(&list(sizes, "window size"), Some(path), "chars")
+        }
+        ["6", "stream", sizes, path, alphabet] => {
+            crate::puzzles::day6::stream(&list(sizes, "window size"), Some(path), alphabet)
+        }
+        ["6", "segment"] => crate::puzzles::day6::segment(4, 14, "chars"),
+        ["6", "segment", packet, message] => crate::puzzles::day6::segment(
+            arg(packet, "packet window size"),
+            arg(message, "message window size"),
+            "chars",
+        ),
+        ["6", "segment", packet, message, alphabet] => crate::puzzles::day6::segment(
+            arg(packet, "packet window size"),
+            arg(message, "message window size"),
+            alphabet,
+        ),
+        ["5", "play", crane, input, delay] => {
+            crate::puzzles::day5::play(crane, input == "real", arg(delay, "delay in milliseconds"))
+        }
+        ["5", "dump", crane, input, path] => {
+            crate::puzzles::day5::dump(crane, input == "real", path)
+        }
+        ["5", "check", crane, path] => crate::puzzles::day5::check(crane, path, false),
+        ["5", "check", crane, path, "skip"] => crate::puzzles::day5::check(crane, path, true),
+        ["5", "costs", crane, input] => crate::puzzles::day5::costs(crane, input == "real"),
+        ["5", "reverse", crane, path] => crate::puzzles::day5::reverse(crane, path),
+        ["9", "knots", knots, input] => {
+            crate::puzzles::day9::knots(arg(knots, "knot count"), input == "real")
+        }
+        ["9", "file", knots, path] => {
+            crate::puzzles::day9::file(arg(knots, "knot count"), path, false)
+        }
+        ["9", "file", knots, path, "3d"] => {
+            crate::puzzles::day9::file(arg(knots, "knot count"), path, true)
+        }
+        ["9", "play", knots, input, delay] => crate::puzzles::day9::play(
+            arg(knots, "knot count"),
+            input == "real",
+            arg(delay, "delay in milliseconds"),
+        ),
+        ["9", "image", knots, input, path] => {
+            crate::puzzles::day9::image(arg(knots, "knot count"), input == "real", path)
+        }
+        _ => return false,
+    }
+    true
+}
@@ -99,7 +99,7 @@ pub fn day_input_raw(day: u8) -> RawInput {
     }
 }
 
-pub fn day_input<T: FromStr>(day: u8) -> DayInput<T>
+pub fn day_input<T>(day: u8) -> DayInput<T>
 where
     T: FromStr,
     <T as FromStr>::Err: Debug,
@@ -112,7 +112,7 @@ where
     }
 }
 
-pub fn day_input_grouped<T: FromStr>(day: u8) -> DayInputGrouped<T>
+pub fn day_input_grouped<T>(day: u8) -> DayInputGrouped<T>
 where
     T: FromStr + Clone,
     <T as FromStr>::Err: Debug,
@@ -124,3 +124,19 @@ where
         real: lines_from_file_grouped::<T>(&real),
     }
 }
+
+pub fn file_input<T>(path: impl AsRef<Path>) -> Vec<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    lines_from_file::<T>(path)
+}
+
+pub fn file_input_grouped<T>(path: impl AsRef<Path>) -> Vec<Vec<T>>
+where
+    T: FromStr + Clone,
+    <T as FromStr>::Err: Debug,
+{
+    lines_from_file_grouped::<T>(path)
+}
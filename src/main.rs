@@ -1,27 +1,48 @@
 #![allow(dead_code)]
 
+use std::env;
+
+mod cli;
 mod input;
 mod print;
 mod puzzles;
 
 fn main() {
+    // Run an extra command if one was given, e.g. `cargo run -- 1 top 5`
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        if !cli::run(&args) {
+            println!("Unknown command: {}", args.join(" "));
+        }
+        return;
+    }
+
     // puzzles::day1::part1();
     // puzzles::day1::part2();
 
     // puzzles::day2::part1();
     // puzzles::day2::part2();
+    // puzzles::day2::breakdown(1, Some("day2.csv"));
+    // puzzles::day2::analysis("rps");
+    // puzzles::day2::decode("rps", "ABC", "XYZ", "XYZ");
 
     // puzzles::day3::part1();
     // puzzles::day3::part2();
+    // puzzles::day3::diagnostics("lowest");
+    // puzzles::day3::with_scheme("priorities.txt");
+    // puzzles::day3::bench();
 
     // puzzles::day4::part1();
     // puzzles::day4::part2();
 
     // puzzles::day5::part1();
     // puzzles::day5::part2();
+    // puzzles::day5::render();
+    // puzzles::day5::verify_reverse();
 
     // puzzles::day6::part1();
     // puzzles::day6::part2();
+    // puzzles::day6::verify();
 
     // puzzles::day7::part1();
     // puzzles::day7::part2();
@@ -31,6 +52,7 @@ fn main() {
 
     // puzzles::day9::part1();
     // puzzles::day9::part2();
+    // puzzles::day9::both_parts();
 
     // puzzles::day10::part1();
     // puzzles::day10::part2();
@@ -1,29 +1,196 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
+
 use crate::input;
 use crate::print;
 
-fn find_max_calories(elves: &[Vec<usize>]) -> usize {
-    let mut max: usize = 0;
-    for elf in elves {
-        let sum = elf.iter().sum();
-        if sum > max {
-            max = sum;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ElfTotal {
+    elf: usize,
+    calories: usize,
+}
+
+// Most elves tied with last place that are listed by number; any more are
+// only counted, so memory stays constant however many elves tie
+const MAX_TIED: usize = 10;
+
+/**
+ * The elves carrying the most calories, highest first. Elves with equal
+ * totals are ranked by position, and any elves left out of the top N despite
+ * matching the last place total are listed separately, up to `MAX_TIED` of
+ * them, with `tied_count` giving the full number.
+ */
+struct TopElves {
+    top: Vec<ElfTotal>,
+    tied: Vec<ElfTotal>,
+    tied_count: usize,
+}
+
+impl TopElves {
+    pub fn sum(&self) -> usize {
+        self.top.iter().map(|e| e.calories).sum()
+    }
+}
+
+impl fmt::Display for TopElves {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (rank, elf) in self.top.iter().enumerate() {
+            writeln!(
+                f,
+                "#{}: elf {} with {}",
+                rank + 1,
+                elf.elf + 1,
+                elf.calories
+            )?;
         }
+        if !self.tied.is_empty() {
+            let elves: Vec<String> = self.tied.iter().map(|e| (e.elf + 1).to_string()).collect();
+            write!(f, "Tied with last place: elf {}", elves.join(", "))?;
+            if self.tied_count > self.tied.len() {
+                write!(f, " and {} more", self.tied_count - self.tied.len())?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "Total: {}", self.sum())
     }
-    max
 }
 
 fn sum_calories(elves: &[Vec<usize>]) -> Vec<usize> {
     elves.iter().map(|elf| elf.iter().sum()).collect()
 }
 
+/**
+ * Keeps track of the N highest totals seen so far, plus any elves tied with
+ * last place, without storing every total
+ */
+struct RunningTop {
+    n: usize,
+    elves: usize,
+    heap: BinaryHeap<Reverse<(usize, Reverse<usize>)>>,
+    tied: Vec<ElfTotal>,
+    tied_count: usize,
+}
+
+impl RunningTop {
+    pub fn new(n: usize) -> RunningTop {
+        RunningTop {
+            n,
+            elves: 0,
+            // Any N is accepted, so only reserve space for modest ones up front
+            heap: BinaryHeap::with_capacity(n.saturating_add(1).min(1024)),
+            tied: vec![],
+            tied_count: 0,
+        }
+    }
+
+    // Records the total for the next elf
+    pub fn push(&mut self, calories: usize) {
+        let elf = self.elves;
+        self.elves += 1;
+        if self.n == 0 {
+            return;
+        }
+
+        // Heap is ordered so the lowest total (and latest elf among equals) is on top
+        self.heap.push(Reverse((calories, Reverse(elf))));
+        if self.heap.len() <= self.n {
+            return;
+        }
+
+        // Keep the evicted elf if it still matches the new last place
+        let Reverse((evicted, Reverse(evicted_elf))) = self.heap.pop().unwrap();
+        let Reverse((cutoff, _)) = self.heap.peek().unwrap();
+        let cutoff = *cutoff;
+
+        // Ties with an old last place no longer count once the cutoff rises
+        if self.tied.first().is_some_and(|e| e.calories != cutoff) {
+            self.tied.clear();
+            self.tied_count = 0;
+        }
+
+        // Only the first ties found are listed; the rest are just counted
+        if evicted == cutoff {
+            self.tied_count += 1;
+            if self.tied.len() < MAX_TIED {
+                self.tied.push(ElfTotal {
+                    elf: evicted_elf,
+                    calories: evicted,
+                });
+            }
+        }
+    }
+
+    pub fn finish(self) -> TopElves {
+        let top = self
+            .heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((calories, Reverse(elf)))| ElfTotal { elf, calories })
+            .collect();
+        let mut tied = self.tied;
+        tied.sort_unstable_by_key(|e| e.elf);
+        TopElves {
+            top,
+            tied,
+            tied_count: self.tied_count,
+        }
+    }
+}
+
+// Finds the N elves with the highest totals
+fn top_n(totals: &[usize], n: usize) -> TopElves {
+    let mut running = RunningTop::new(n);
+    for &calories in totals {
+        running.push(calories);
+    }
+    running.finish()
+}
+
+// Finds the N elves with the highest totals from groups of lines in a reader,
+// one line at a time, so input of any size can be processed
+fn top_n_streamed<R: BufRead>(reader: R, n: usize) -> io::Result<TopElves> {
+    let mut running = RunningTop::new(n);
+    let mut group: Option<usize> = None;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            if let Some(total) = group.take() {
+                running.push(total);
+            }
+            continue;
+        }
+
+        let calories: usize = line.parse().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Line {}: invalid calories {:?} ({})", index + 1, line, e),
+            )
+        })?;
+        group = Some(group.unwrap_or(0) + calories);
+    }
+
+    // Ensure final group is pushed if no newline at end of input
+    if let Some(total) = group {
+        running.push(total);
+    }
+
+    Ok(running.finish())
+}
+
 // PART 1
 // Find the elf holding the most calories worth of food, and sum their total
 pub fn part1() {
     print::intro(1, 1);
     let data = input::day_input_grouped::<usize>(1);
 
-    let max_test = find_max_calories(&data.test);
-    let max = find_max_calories(&data.real);
+    let max_test = top_n(&sum_calories(&data.test), 1).sum();
+    let max = top_n(&sum_calories(&data.real), 1).sum();
 
     print::answer_with_test(max, max_test);
 }
@@ -34,16 +201,219 @@ pub fn part2() {
     print::intro(1, 2);
     let data = input::day_input_grouped::<usize>(1);
 
-    let mut totals = sum_calories(&data.real);
-    totals.sort_unstable();
-    totals.reverse();
+    let sum_test = top_n(&sum_calories(&data.test), 3).sum();
+    let sum = top_n(&sum_calories(&data.real), 3).sum();
+
+    print::answer_with_test(sum, sum_test);
+}
 
-    let mut sum = 0;
-    for i in 0..3 {
-        if let Some(total) = totals.get(i) {
-            sum += total;
+// TOP N
+// List the N elves with the most calories, including any ties for last place
+pub fn top(n: usize) {
+    println!("=== DAY 1, TOP {} ===", n);
+    let data = input::day_input_grouped::<usize>(1);
+
+    let top_test = top_n(&sum_calories(&data.test), n);
+    let top = top_n(&sum_calories(&data.real), n);
+
+    print::answer_with_test_newline(top, top_test);
+}
+
+// STREAM
+// Find the top N elves from a file or stdin without loading it all into memory
+pub fn stream(n: usize, path: Option<&str>) {
+    println!("=== DAY 1, STREAMED TOP {} ===", n);
+
+    let result = match path {
+        Some(path) if path != "-" => {
+            File::open(path).and_then(|file| top_n_streamed(BufReader::new(file), n))
+        }
+        _ => top_n_streamed(io::stdin().lock(), n),
+    };
+
+    match result {
+        Ok(top) => println!("{}\n", top),
+        Err(e) => println!("Error: {}\n", e),
+    }
+}
+
+// STATISTICS
+// Summarise the calories carried by every elf
+
+#[derive(Clone, Copy)]
+enum StatsFormat {
+    Table,
+    Json,
+}
+
+impl FromStr for StatsFormat {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(StatsFormat::Table),
+            "json" => Ok(StatsFormat::Json),
+            _ => Err(format!("Unknown format {:?}, expected table or json", s)),
+        }
+    }
+    type Err = String;
+}
+
+struct Bucket {
+    from: usize,
+    to: usize,
+    elves: usize,
+}
+
+struct CalorieStats {
+    elves: usize,
+    mean: f64,
+    median: f64,
+    std_dev: f64,
+    histogram: Vec<Bucket>,
+    most_items: Option<(usize, usize)>,
+    largest_snack: Option<(usize, usize)>,
+}
+
+impl CalorieStats {
+    pub fn new(elves: &[Vec<usize>], buckets: usize) -> CalorieStats {
+        let mut totals = sum_calories(elves);
+        let count = totals.len();
+        totals.sort_unstable();
+
+        let mean = totals.iter().sum::<usize>() as f64 / count.max(1) as f64;
+        let variance = totals
+            .iter()
+            .map(|&t| (t as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count.max(1) as f64;
+        let median = match count {
+            0 => 0.0,
+            n if n % 2 == 0 => (totals[n / 2 - 1] + totals[n / 2]) as f64 / 2.0,
+            n => totals[n / 2] as f64,
+        };
+
+        // Equal width buckets spanning the lowest to highest total
+        let mut histogram = vec![];
+        if let (Some(&min), Some(&max), true) = (totals.first(), totals.last(), buckets > 0) {
+            let width = (max - min) / buckets + 1;
+            histogram = (0..buckets)
+                .map(|i| Bucket {
+                    from: min + i * width,
+                    to: min + (i + 1) * width - 1,
+                    elves: 0,
+                })
+                .collect();
+            for total in &totals {
+                histogram[(total - min) / width].elves += 1;
+            }
+        }
+
+        // Elf with the most items, and elf with the single largest snack; the
+        // first elf wins any ties
+        let most_items = elves
+            .iter()
+            .enumerate()
+            .map(|(elf, items)| (elf, items.len()))
+            .rev()
+            .max_by_key(|&(_, items)| items);
+        let largest_snack = elves
+            .iter()
+            .enumerate()
+            .filter_map(|(elf, items)| items.iter().max().map(|&snack| (elf, snack)))
+            .rev()
+            .max_by_key(|&(_, snack)| snack);
+
+        CalorieStats {
+            elves: count,
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            histogram,
+            most_items,
+            largest_snack,
         }
     }
 
-    print::answer(sum);
+    // Writes the statistics as a JSON object, indenting nested lines
+    fn to_json(&self, indent: &str) -> String {
+        let pair = |value: Option<(usize, usize)>, key: &str| match value {
+            Some((elf, n)) => format!("{{\"elf\": {}, \"{}\": {}}}", elf + 1, key, n),
+            None => String::from("null"),
+        };
+        let histogram: Vec<String> = self
+            .histogram
+            .iter()
+            .map(|b| {
+                format!(
+                    "{{\"from\": {}, \"to\": {}, \"elves\": {}}}",
+                    b.from, b.to, b.elves
+                )
+            })
+            .collect();
+
+        let fields = [
+            format!("\"elves\": {}", self.elves),
+            format!("\"mean\": {:.4}", self.mean),
+            format!("\"median\": {:.1}", self.median),
+            format!("\"std_dev\": {:.4}", self.std_dev),
+            format!("\"most_items\": {}", pair(self.most_items, "items")),
+            format!(
+                "\"largest_snack\": {}",
+                pair(self.largest_snack, "calories")
+            ),
+            format!("\"histogram\": [{}]", histogram.join(", ")),
+        ];
+        let separator = format!(",\n{}  ", indent);
+        format!("{{\n{}  {}\n{}}}", indent, fields.join(&separator), indent)
+    }
+}
+
+// Lists the statistics as a table, with a bar chart of the histogram
+impl fmt::Display for CalorieStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16}{}", "Elves", self.elves)?;
+        writeln!(f, "{:<16}{:.2}", "Mean", self.mean)?;
+        writeln!(f, "{:<16}{:.1}", "Median", self.median)?;
+        writeln!(f, "{:<16}{:.2}", "Std dev", self.std_dev)?;
+        if let Some((elf, items)) = self.most_items {
+            writeln!(f, "{:<16}elf {} ({} items)", "Most items", elf + 1, items)?;
+        }
+        if let Some((elf, snack)) = self.largest_snack {
+            writeln!(f, "{:<16}elf {} ({})", "Largest snack", elf + 1, snack)?;
+        }
+
+        write!(f, "Histogram")?;
+        let widest = self.histogram.iter().map(|b| b.elves).max().unwrap_or(0);
+        for bucket in &self.histogram {
+            let bar = "#".repeat((bucket.elves * 40).div_ceil(widest.max(1)));
+            let line = format!(
+                "{:>7} - {:<7} {:>5} {}",
+                bucket.from, bucket.to, bucket.elves, bar
+            );
+            write!(f, "\n  {}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+pub fn stats(format: &str, buckets: usize) {
+    let format = match format.parse::<StatsFormat>() {
+        Ok(format) => format,
+        Err(e) => return println!("{}\n", e),
+    };
+    let data = input::day_input_grouped::<usize>(1);
+    let stats_test = CalorieStats::new(&data.test, buckets);
+    let stats = CalorieStats::new(&data.real, buckets);
+
+    match format {
+        StatsFormat::Table => {
+            println!("=== DAY 1, STATISTICS ===");
+            print::answer_with_test_newline(stats, stats_test);
+        }
+        // Only the JSON itself, so the output can be piped straight to a parser
+        StatsFormat::Json => println!(
+            "{{\n  \"test\": {},\n  \"real\": {}\n}}",
+            stats_test.to_json("  "),
+            stats.to_json("  ")
+        ),
+    }
 }
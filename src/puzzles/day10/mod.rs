@@ -68,7 +68,6 @@ impl fmt::Display for Screen {
 /**
  * Handheld device; can run CPU cycles
  */
-
 struct Device {
     cycle: i32,
     register: i32,
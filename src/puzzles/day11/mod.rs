@@ -162,7 +162,7 @@ impl Monkey {
     }
 
     fn test(&self, number: u64) -> bool {
-        number % self.divisor == 0
+        number.is_multiple_of(self.divisor)
     }
 
     fn get_target(&self, number: u64) -> usize {
@@ -1,103 +1,523 @@
 use crate::input;
 use crate::print;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
 use std::str::FromStr;
-use std::string::ParseError;
 
-#[derive(Debug, Copy, Clone)]
-enum Action {
-    Rock = 1,
-    Paper = 2,
-    Scissors = 3,
-}
+// A move is identified by its index within a game
+type Move = usize;
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Outcome {
-    Loss = 0,
-    Draw = 3,
-    Win = 6,
+    Loss,
+    Draw,
+    Win,
+}
+
+/**
+ * A game where moves, the "beats" relation and scoring are all data. Playing
+ * a move scores its shape points, plus points for the outcome of the round.
+ */
+struct Game {
+    names: Vec<String>,
+    shape_points: Vec<isize>,
+    beats: Vec<Vec<bool>>,
+    outcome_points: [isize; 3],
+}
+
+impl Game {
+    // Creates a game where each (winner, loser) pair describes one move beating another
+    pub fn new(moves: &[(&str, isize)], beats: &[(Move, Move)]) -> Game {
+        let mut table = vec![vec![false; moves.len()]; moves.len()];
+        for &(winner, loser) in beats {
+            table[winner][loser] = true;
+        }
+        Game {
+            names: moves.iter().map(|(name, _)| name.to_string()).collect(),
+            shape_points: moves.iter().map(|(_, points)| *points).collect(),
+            beats: table,
+            outcome_points: [0, 3, 6],
+        }
+    }
+
+    // Creates a cyclic game from an odd number of moves, where each move beats
+    // the half of the other moves that come before it (wrapping around).
+    // Moves score 1, 2, 3, ... in the order given.
+    pub fn cyclic(names: &[&str]) -> Game {
+        let count = names.len();
+        assert!(count % 2 == 1, "Cyclic games need an odd number of moves");
+
+        let moves: Vec<(&str, isize)> = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (*name, index as isize + 1))
+            .collect();
+        let beats: Vec<(Move, Move)> = (0..count)
+            .flat_map(|winner| {
+                (1..=count / 2).map(move |offset| (winner, (winner + count - offset) % count))
+            })
+            .collect();
+
+        Game::new(&moves, &beats)
+    }
+
+    pub fn rock_paper_scissors() -> Game {
+        Game::cyclic(&["Rock", "Paper", "Scissors"])
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Game {
+        Game::cyclic(&["Rock", "Spock", "Paper", "Lizard", "Scissors"])
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn name(&self, action: Move) -> &str {
+        &self.names[action]
+    }
+
+    // Determines the outcome of a round from the player's perspective
+    pub fn outcome(&self, player: Move, opponent: Move) -> Outcome {
+        if self.beats[player][opponent] {
+            Outcome::Win
+        } else if self.beats[opponent][player] {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    pub fn shape_points(&self, action: Move) -> isize {
+        self.shape_points[action]
+    }
+
+    pub fn outcome_points(&self, outcome: Outcome) -> isize {
+        self.outcome_points[outcome as usize]
+    }
+
+    // Scores a round from the player's perspective
+    pub fn score(&self, actions: &RoundActions) -> isize {
+        let outcome = self.outcome(actions.player, actions.opponent);
+        self.shape_points(actions.player) + self.outcome_points(outcome)
+    }
+
+    // Finds the first move that achieves an outcome against the opponent's move
+    pub fn move_for_outcome(&self, opponent: Move, outcome: Outcome) -> Option<Move> {
+        (0..self.move_count()).find(|&player| self.outcome(player, opponent) == outcome)
+    }
+}
+
+impl FromStr for Game {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rps" => Ok(Game::rock_paper_scissors()),
+            "rpsls" => Ok(Game::rock_paper_scissors_lizard_spock()),
+            _ => Err(format!("Unknown game {:?}, expected rps or rpsls", s)),
+        }
+    }
+    type Err = String;
+}
+
+#[derive(Debug)]
+enum GuideError {
+    MissingColumn {
+        line: usize,
+    },
+    MalformedColumn {
+        line: usize,
+        token: String,
+    },
+    UnknownLetter {
+        line: usize,
+        letter: char,
+        column: &'static str,
+    },
+    DuplicateLetter {
+        letter: char,
+        column: &'static str,
+    },
+    UnknownMove {
+        letter: char,
+        action: Move,
+        moves: usize,
+    },
+    TooManyLetters {
+        column: &'static str,
+        letters: usize,
+        allowed: usize,
+    },
+}
+
+impl fmt::Display for GuideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuideError::MissingColumn { line } => {
+                write!(f, "Line {}: expected two columns", line)
+            }
+            GuideError::MalformedColumn { line, token } => {
+                write!(
+                    f,
+                    "Line {}: expected a single letter, found {:?}",
+                    line, token
+                )
+            }
+            GuideError::UnknownLetter {
+                line,
+                letter,
+                column,
+            } => write!(f, "Line {}: unknown {} letter {:?}", line, column, letter),
+            GuideError::DuplicateLetter { letter, column } => {
+                write!(f, "Letter {:?} is mapped twice for {}", letter, column)
+            }
+            GuideError::UnknownMove {
+                letter,
+                action,
+                moves,
+            } => write!(
+                f,
+                "Letter {:?} maps to move {}, but the game only has {} moves",
+                letter, action, moves
+            ),
+            GuideError::TooManyLetters {
+                column,
+                letters,
+                allowed,
+            } => write!(
+                f,
+                "Mapping for {} has {} letters, but only {} are allowed",
+                column, letters, allowed
+            ),
+        }
+    }
 }
 
 struct RoundInputs {
+    line: usize,
     player: char,
     opponent: char,
 }
 
 struct RoundActions {
-    player: Action,
-    opponent: Action,
+    player: Move,
+    opponent: Move,
 }
 
-type ActionResolver = dyn Fn(&RoundInputs) -> RoundActions;
+type ActionResolver<'a> =
+    dyn Fn(&Game, &Decoder, &RoundInputs) -> Result<RoundActions, GuideError> + 'a;
 
 // Round parsing
 
-impl FromStr for RoundInputs {
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s
-            .split_ascii_whitespace()
-            .map(|c| c.chars().next().unwrap());
-        let opponent = chars.next().expect("Missing player move");
-        let player = chars.next().expect("Missing opponent move");
-        Ok(RoundInputs { player, opponent })
+impl RoundInputs {
+    pub fn new(line: usize, s: &str) -> Result<RoundInputs, GuideError> {
+        let parse_letter = |token: Option<&str>| {
+            let token = token.ok_or(GuideError::MissingColumn { line })?;
+            let mut chars = token.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(GuideError::MalformedColumn {
+                    line,
+                    token: token.to_string(),
+                }),
+            }
+        };
+
+        let mut tokens = s.split_ascii_whitespace();
+        let opponent = parse_letter(tokens.next())?;
+        let player = parse_letter(tokens.next())?;
+        if let Some(token) = tokens.next() {
+            return Err(GuideError::MalformedColumn {
+                line,
+                token: token.to_string(),
+            });
+        }
+
+        Ok(RoundInputs {
+            line,
+            player,
+            opponent,
+        })
+    }
+}
+
+// Parses a strategy guide, numbering lines from 1 and skipping blank ones
+fn parse_guide(raw: &str) -> Result<Vec<RoundInputs>, GuideError> {
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| RoundInputs::new(index + 1, line))
+        .collect()
+}
+
+fn load_guides() -> (Vec<RoundInputs>, Vec<RoundInputs>) {
+    let data = input::day_input_raw(2);
+    (
+        checked(parse_guide(&data.test)),
+        checked(parse_guide(&data.real)),
+    )
+}
+
+// Unwraps a result, panicking with a readable message for a corrupt guide
+fn checked<T>(result: Result<T, GuideError>) -> T {
+    result.unwrap_or_else(|e| panic!("Invalid strategy guide: {}", e))
+}
+
+/**
+ * Maps the letters in a strategy guide to moves and outcomes, rejecting any
+ * letter it doesn't know about
+ */
+#[derive(Clone)]
+struct Decoder {
+    opponent: HashMap<char, Move>,
+    player: HashMap<char, Move>,
+    outcome: HashMap<char, Outcome>,
+}
+
+// Builds a letter table, rejecting letters mapped twice and values beyond
+// the number allowed
+fn letter_table<T: Copy>(
+    pairs: impl Iterator<Item = (char, T)>,
+    column: &'static str,
+) -> Result<HashMap<char, T>, GuideError> {
+    let mut table = HashMap::new();
+    for (letter, value) in pairs {
+        if table.insert(letter, value).is_some() {
+            return Err(GuideError::DuplicateLetter { letter, column });
+        }
+    }
+    Ok(table)
+}
+
+// Checks a string has no more letters than there are things to map them to
+fn check_letters(letters: &str, column: &'static str, allowed: usize) -> Result<(), GuideError> {
+    let count = letters.chars().count();
+    if count > allowed {
+        return Err(GuideError::TooManyLetters {
+            column,
+            letters: count,
+            allowed,
+        });
+    }
+    Ok(())
+}
+
+impl Decoder {
+    // Creates a decoder from the letters for each move, in game order, and the
+    // letters for each outcome, in loss/draw/win order
+    pub fn new(
+        game: &Game,
+        opponent: &str,
+        player: &str,
+        outcome: &str,
+    ) -> Result<Decoder, GuideError> {
+        let outcomes = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+        check_letters(opponent, "opponent move", game.move_count())?;
+        check_letters(player, "player move", game.move_count())?;
+        check_letters(outcome, "outcome", outcomes.len())?;
+
+        Ok(Decoder {
+            opponent: letter_table(opponent.chars().zip(0..), "opponent move")?,
+            player: letter_table(player.chars().zip(0..), "player move")?,
+            outcome: letter_table(outcome.chars().zip(outcomes), "outcome")?,
+        })
+    }
+
+    // The puzzle's mapping: A/B/C and X/Y/Z for rock/paper/scissors, with
+    // X/Y/Z meaning lose/draw/win when the second column is an outcome
+    pub fn standard(game: &Game) -> Result<Decoder, GuideError> {
+        Decoder::new(game, "ABC", "XYZ", "XYZ")
+    }
+
+    // Creates a copy of this decoder using a different mapping for player moves
+    pub fn with_player(
+        &self,
+        game: &Game,
+        mapping: &[(char, Move)],
+    ) -> Result<Decoder, GuideError> {
+        if let Some(&(letter, action)) = mapping.iter().find(|(_, a)| *a >= game.move_count()) {
+            return Err(GuideError::UnknownMove {
+                letter,
+                action,
+                moves: game.move_count(),
+            });
+        }
+        Ok(Decoder {
+            player: letter_table(mapping.iter().copied(), "player move")?,
+            ..self.clone()
+        })
+    }
+
+    fn decode<T: Copy>(
+        table: &HashMap<char, T>,
+        round: &RoundInputs,
+        letter: char,
+        column: &'static str,
+    ) -> Result<T, GuideError> {
+        table
+            .get(&letter)
+            .copied()
+            .ok_or(GuideError::UnknownLetter {
+                line: round.line,
+                letter,
+                column,
+            })
+    }
+
+    pub fn opponent(&self, round: &RoundInputs) -> Result<Move, GuideError> {
+        Decoder::decode(&self.opponent, round, round.opponent, "opponent move")
+    }
+
+    pub fn player(&self, round: &RoundInputs) -> Result<Move, GuideError> {
+        Decoder::decode(&self.player, round, round.player, "player move")
+    }
+
+    pub fn outcome(&self, round: &RoundInputs) -> Result<Outcome, GuideError> {
+        Decoder::decode(&self.outcome, round, round.player, "outcome")
+    }
+
+    pub fn opponents(&self, rounds: &[RoundInputs]) -> Result<Vec<Move>, GuideError> {
+        rounds.iter().map(|round| self.opponent(round)).collect()
     }
-    type Err = ParseError;
 }
 
 // Tournament simulation
 
-fn simulate_round(actions: &RoundActions) -> isize {
-    let points_shape = actions.player as isize;
-    let points_outcome = match actions.opponent {
-        Action::Rock => match actions.player {
-            Action::Rock => Outcome::Draw,
-            Action::Paper => Outcome::Win,
-            Action::Scissors => Outcome::Loss,
-        },
-        Action::Paper => match actions.player {
-            Action::Rock => Outcome::Loss,
-            Action::Paper => Outcome::Draw,
-            Action::Scissors => Outcome::Win,
-        },
-        Action::Scissors => match actions.player {
-            Action::Rock => Outcome::Win,
-            Action::Paper => Outcome::Loss,
-            Action::Scissors => Outcome::Draw,
-        },
-    } as isize;
-    points_shape + points_outcome
-}
-
-fn simulate_tournament(rounds: &Vec<RoundInputs>, action_resolver: &ActionResolver) -> isize {
+fn simulate_tournament(
+    game: &Game,
+    decoder: &Decoder,
+    rounds: &[RoundInputs],
+    action_resolver: &ActionResolver<'_>,
+) -> Result<isize, GuideError> {
     let mut score = 0;
     for round in rounds {
-        let actions = action_resolver(round);
-        score += simulate_round(&actions);
+        let actions = action_resolver(game, decoder, round)?;
+        score += game.score(&actions);
     }
-    score
+    Ok(score)
 }
 
-// PART 1
-// Simulate tournament using default move resolver
+// Tournament breakdown
+
+struct RoundReport {
+    opponent: Move,
+    player: Move,
+    outcome: Outcome,
+    shape_points: isize,
+    outcome_points: isize,
+}
 
-fn resolve_basic(inputs: &RoundInputs) -> RoundActions {
-    fn map_char(c: char) -> Action {
-        match c {
-            'A' | 'X' => Action::Rock,
-            'B' | 'Y' => Action::Paper,
-            _ => Action::Scissors,
+struct TournamentReport {
+    moves: Vec<String>,
+    rounds: Vec<RoundReport>,
+}
+
+impl TournamentReport {
+    pub fn new(
+        game: &Game,
+        decoder: &Decoder,
+        rounds: &[RoundInputs],
+        action_resolver: &ActionResolver<'_>,
+    ) -> Result<TournamentReport, GuideError> {
+        let rounds = rounds
+            .iter()
+            .map(|round| {
+                let actions = action_resolver(game, decoder, round)?;
+                let outcome = game.outcome(actions.player, actions.opponent);
+                Ok(RoundReport {
+                    opponent: actions.opponent,
+                    player: actions.player,
+                    outcome,
+                    shape_points: game.shape_points(actions.player),
+                    outcome_points: game.outcome_points(outcome),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(TournamentReport {
+            moves: game.names.clone(),
+            rounds,
+        })
+    }
+
+    pub fn total(&self) -> isize {
+        self.rounds
+            .iter()
+            .map(|r| r.shape_points + r.outcome_points)
+            .sum()
+    }
+
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.rounds.iter().filter(|r| r.outcome == outcome).count()
+    }
+
+    // Total points scored in rounds where the player used each move
+    pub fn points_by_shape(&self) -> Vec<isize> {
+        let mut points = vec![0; self.moves.len()];
+        for round in &self.rounds {
+            points[round.player] += round.shape_points + round.outcome_points;
+        }
+        points
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("round,opponent,player,outcome,shape_points,outcome_points,score\n");
+        for (index, round) in self.rounds.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{:?},{},{},{}\n",
+                index + 1,
+                self.moves[round.opponent],
+                self.moves[round.player],
+                round.outcome,
+                round.shape_points,
+                round.outcome_points,
+                round.shape_points + round.outcome_points
+            ));
         }
+        csv
     }
-    RoundActions {
-        player: map_char(inputs.player),
-        opponent: map_char(inputs.opponent),
+}
+
+impl fmt::Display for TournamentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rounds: {}", self.rounds.len())?;
+        writeln!(
+            f,
+            "Wins: {}, draws: {}, losses: {}",
+            self.count(Outcome::Win),
+            self.count(Outcome::Draw),
+            self.count(Outcome::Loss)
+        )?;
+        for (name, points) in self.moves.iter().zip(self.points_by_shape()) {
+            writeln!(f, "Points playing {}: {}", name, points)?;
+        }
+        write!(f, "Total: {}", self.total())
     }
 }
 
+// PART 1
+// Simulate tournament using default move resolver
+
+fn resolve_basic(
+    _game: &Game,
+    decoder: &Decoder,
+    inputs: &RoundInputs,
+) -> Result<RoundActions, GuideError> {
+    Ok(RoundActions {
+        player: decoder.player(inputs)?,
+        opponent: decoder.opponent(inputs)?,
+    })
+}
+
 pub fn part1() {
     print::intro(2, 1);
-    let data = input::day_input::<RoundInputs>(2);
+    let (test, real) = load_guides();
+    let game = Game::rock_paper_scissors();
+    let decoder = checked(Decoder::standard(&game));
 
-    let sum_test = simulate_tournament(&data.test, &resolve_basic);
-    let sum = simulate_tournament(&data.real, &resolve_basic);
+    let sum_test = checked(simulate_tournament(&game, &decoder, &test, &resolve_basic));
+    let sum = checked(simulate_tournament(&game, &decoder, &real, &resolve_basic));
 
     print::answer_with_test(sum, sum_test);
 }
@@ -105,43 +525,324 @@ pub fn part1() {
 // PART 2
 // Second column dictates how round should end
 
-fn resolve_for_outcome(inputs: &RoundInputs) -> RoundActions {
-    let opponent: Action = match inputs.opponent {
-        'A' => Action::Rock,
-        'B' => Action::Paper,
-        _ => Action::Scissors,
+fn resolve_for_outcome(
+    game: &Game,
+    decoder: &Decoder,
+    inputs: &RoundInputs,
+) -> Result<RoundActions, GuideError> {
+    let opponent = decoder.opponent(inputs)?;
+    let outcome = decoder.outcome(inputs)?;
+    let player = game
+        .move_for_outcome(opponent, outcome)
+        .expect("No move achieves outcome");
+    Ok(RoundActions { player, opponent })
+}
+
+pub fn part2() {
+    print::intro(2, 2);
+    let (test, real) = load_guides();
+    let game = Game::rock_paper_scissors();
+    let decoder = checked(Decoder::standard(&game));
+
+    let sum_test = checked(simulate_tournament(
+        &game,
+        &decoder,
+        &test,
+        &resolve_for_outcome,
+    ));
+    let sum = checked(simulate_tournament(
+        &game,
+        &decoder,
+        &real,
+        &resolve_for_outcome,
+    ));
+
+    print::answer_with_test(sum, sum_test);
+}
+
+// BREAKDOWN
+// Report every round of a part's tournament, optionally exporting it as CSV
+
+pub fn breakdown(part: u8, csv_path: Option<&str>) {
+    println!("=== DAY 2, PART {} BREAKDOWN ===", part);
+    let (test, real) = load_guides();
+    let game = Game::rock_paper_scissors();
+    let decoder = checked(Decoder::standard(&game));
+    let resolver: &ActionResolver = match part {
+        1 => &resolve_basic,
+        _ => &resolve_for_outcome,
     };
-    let player: Action = match inputs.player {
-        'X' => {
-            // Should lose
-            match opponent {
-                Action::Rock => Action::Scissors,
-                Action::Paper => Action::Rock,
-                Action::Scissors => Action::Paper,
+
+    let report_test = checked(TournamentReport::new(&game, &decoder, &test, resolver));
+    let report = checked(TournamentReport::new(&game, &decoder, &real, resolver));
+
+    if let Some(path) = csv_path {
+        match fs::write(path, report.to_csv()) {
+            Ok(_) => println!("Wrote {} rounds to {}", report.rounds.len(), path),
+            Err(e) => println!("Unable to write {}: {}", path, e),
+        }
+    }
+
+    print::answer_with_test_newline(report, report_test);
+}
+
+// ANALYSIS
+// Explore what the second column of the strategy guide could have meant
+
+// Scores the best (or worst) move in every round, given the opponent's moves
+fn extreme_score(game: &Game, opponents: &[Move], best: bool) -> isize {
+    opponents
+        .iter()
+        .map(|&opponent| {
+            let scores =
+                (0..game.move_count()).map(|player| game.score(&RoundActions { player, opponent }));
+            if best {
+                scores.max().unwrap()
+            } else {
+                scores.min().unwrap()
             }
+        })
+        .sum()
+}
+
+/**
+ * Distribution of total scores if the player picked a move uniformly at
+ * random every round
+ */
+struct ScoreDistribution {
+    min: isize,
+    probabilities: Vec<f64>,
+}
+
+impl ScoreDistribution {
+    pub fn new(game: &Game, opponents: &[Move]) -> ScoreDistribution {
+        let moves = game.move_count();
+        let min = extreme_score(game, opponents, false);
+
+        // Probabilities of each score above the minimum, one round at a time
+        let mut probabilities = vec![1.0];
+        for &opponent in opponents {
+            let scores: Vec<isize> = (0..moves)
+                .map(|player| game.score(&RoundActions { player, opponent }))
+                .collect();
+            let floor = *scores.iter().min().unwrap();
+            let spread = (*scores.iter().max().unwrap() - floor) as usize;
+
+            let mut next = vec![0.0; probabilities.len() + spread];
+            for (total, p) in probabilities.iter().enumerate() {
+                for score in &scores {
+                    next[total + (score - floor) as usize] += p / moves as f64;
+                }
+            }
+            probabilities = next;
         }
-        'Y' => {
-            // Should draw
-            opponent
+
+        ScoreDistribution { min, probabilities }
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.scores().map(|(score, p)| score as f64 * p).sum()
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        self.scores()
+            .map(|(score, p)| (score as f64 - mean).powi(2) * p)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    // Finds the lowest score at or below which a fraction of outcomes fall
+    pub fn percentile(&self, fraction: f64) -> isize {
+        let mut cumulative = 0.0;
+        for (score, p) in self.scores() {
+            cumulative += p;
+            if cumulative >= fraction {
+                return score;
+            }
         }
-        _ => {
-            // Should win
-            match opponent {
-                Action::Rock => Action::Paper,
-                Action::Paper => Action::Scissors,
-                Action::Scissors => Action::Rock,
+        self.min + self.probabilities.len() as isize - 1
+    }
+
+    // Probability of scoring at least a given total
+    pub fn probability_at_least(&self, target: isize) -> f64 {
+        self.scores()
+            .filter(|(score, _)| *score >= target)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    fn scores(&self) -> impl Iterator<Item = (isize, f64)> + '_ {
+        self.probabilities
+            .iter()
+            .enumerate()
+            .map(|(offset, p)| (self.min + offset as isize, *p))
+    }
+}
+
+// Scores a guide where each letter in the second column maps to a move
+fn score_with_mapping(
+    game: &Game,
+    decoder: &Decoder,
+    rounds: &[RoundInputs],
+    mapping: &[(char, Move)],
+) -> Result<isize, GuideError> {
+    let decoder = decoder.with_player(game, mapping)?;
+    simulate_tournament(game, &decoder, rounds, &resolve_basic)
+}
+
+// Searches every way of assigning distinct moves to the letters in the second
+// column, returning the highest scoring mapping
+fn find_best_mapping(
+    game: &Game,
+    decoder: &Decoder,
+    rounds: &[RoundInputs],
+) -> Result<(Vec<(char, Move)>, isize), GuideError> {
+    let mut letters: Vec<char> = rounds.iter().map(|r| r.player).collect();
+    letters.sort_unstable();
+    letters.dedup();
+
+    fn search(
+        game: &Game,
+        decoder: &Decoder,
+        rounds: &[RoundInputs],
+        letters: &[char],
+        mapping: &mut Vec<(char, Move)>,
+        best: &mut Option<(Vec<(char, Move)>, isize)>,
+    ) -> Result<(), GuideError> {
+        let Some(&letter) = letters.get(mapping.len()) else {
+            let score = score_with_mapping(game, decoder, rounds, mapping)?;
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_score)| score > *best_score)
+            {
+                *best = Some((mapping.clone(), score));
+            }
+            return Ok(());
+        };
+        for action in 0..game.move_count() {
+            if mapping.iter().all(|(_, used)| *used != action) {
+                mapping.push((letter, action));
+                search(game, decoder, rounds, letters, mapping, best)?;
+                mapping.pop();
             }
         }
+        Ok(())
+    }
+
+    let mut best = None;
+    search(game, decoder, rounds, &letters, &mut vec![], &mut best)?;
+    Ok(best.unwrap_or((vec![], 0)))
+}
+
+struct StrategyAnalysis {
+    guide: isize,
+    best: isize,
+    worst: isize,
+    random_mean: f64,
+    random_std_dev: f64,
+    random_range: (isize, isize),
+    guide_chance: f64,
+    best_mapping: Vec<String>,
+    best_mapping_score: isize,
+}
+
+impl StrategyAnalysis {
+    pub fn new(
+        game: &Game,
+        decoder: &Decoder,
+        rounds: &[RoundInputs],
+    ) -> Result<StrategyAnalysis, GuideError> {
+        let opponents = decoder.opponents(rounds)?;
+        let guide = simulate_tournament(game, decoder, rounds, &resolve_basic)?;
+        let distribution = ScoreDistribution::new(game, &opponents);
+        let (mapping, best_mapping_score) = find_best_mapping(game, decoder, rounds)?;
+
+        Ok(StrategyAnalysis {
+            guide,
+            best: extreme_score(game, &opponents, true),
+            worst: extreme_score(game, &opponents, false),
+            random_mean: distribution.mean(),
+            random_std_dev: distribution.std_dev(),
+            random_range: (distribution.percentile(0.05), distribution.percentile(0.95)),
+            guide_chance: distribution.probability_at_least(guide),
+            best_mapping: mapping
+                .iter()
+                .map(|(letter, action)| format!("{} => {}", letter, game.name(*action)))
+                .collect(),
+            best_mapping_score,
+        })
+    }
+}
+
+impl fmt::Display for StrategyAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Guide score:    {}", self.guide)?;
+        writeln!(f, "Best possible:  {}", self.best)?;
+        writeln!(f, "Worst possible: {}", self.worst)?;
+        writeln!(
+            f,
+            "Random play:    {:.1} ± {:.1} (90% between {} and {})",
+            self.random_mean, self.random_std_dev, self.random_range.0, self.random_range.1
+        )?;
+        writeln!(
+            f,
+            "Chance of matching guide at random: {:.4}%",
+            self.guide_chance * 100.0
+        )?;
+        write!(
+            f,
+            "Best mapping:   {} ({})",
+            self.best_mapping.join(", "),
+            self.best_mapping_score
+        )
+    }
+}
+
+pub fn analysis(game: &str) {
+    println!("=== DAY 2, ANALYSIS ===");
+    let game: Game = match game.parse() {
+        Ok(game) => game,
+        Err(e) => return println!("{}\n", e),
     };
-    RoundActions { player, opponent }
+    let (test, real) = load_guides();
+    let decoder = checked(Decoder::standard(&game));
+
+    let analysis_test = checked(StrategyAnalysis::new(&game, &decoder, &test));
+    let analysis = checked(StrategyAnalysis::new(&game, &decoder, &real));
+
+    print::answer_with_test_newline(analysis, analysis_test);
 }
 
-pub fn part2() {
-    print::intro(2, 2);
-    let data = input::day_input::<RoundInputs>(2);
+// DECODE
+// Play both parts using different letters for the strategy guide's columns
 
-    let sum_test = simulate_tournament(&data.test, &resolve_for_outcome);
-    let sum = simulate_tournament(&data.real, &resolve_for_outcome);
+pub fn decode(game: &str, opponent: &str, player: &str, outcome: &str) {
+    println!(
+        "=== DAY 2, DECODE {} {} {} {} ===",
+        game, opponent, player, outcome
+    );
+    let game: Game = match game.parse() {
+        Ok(game) => game,
+        Err(e) => return println!("{}\n", e),
+    };
+    let (test, real) = load_guides();
+    let decoder = match Decoder::new(&game, opponent, player, outcome) {
+        Ok(decoder) => decoder,
+        Err(e) => return println!("{}\n", e),
+    };
 
-    print::answer_with_test(sum, sum_test);
+    let resolvers: [(&str, &ActionResolver); 2] =
+        [("Part 1", &resolve_basic), ("Part 2", &resolve_for_outcome)];
+    for (name, resolver) in resolvers {
+        let score_test = simulate_tournament(&game, &decoder, &test, resolver);
+        let score = simulate_tournament(&game, &decoder, &real, resolver);
+        match (score, score_test) {
+            (Ok(score), Ok(score_test)) => {
+                println!("{}: {} (test {})", name, score, score_test)
+            }
+            (Err(e), _) | (_, Err(e)) => println!("{}: {}", name, e),
+        }
+    }
+    println!();
 }
@@ -1,8 +1,10 @@
-use std::collections::hash_set::Iter;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::str::FromStr;
-use std::string::ParseError;
+use std::time::Instant;
 
 use crate::input;
 use crate::print;
@@ -10,170 +12,689 @@ use crate::print;
 const BASE_PRIORITY_LOWER: usize = 'a' as usize;
 const BASE_PRIORITY_UPPER: usize = 'A' as usize;
 
-#[derive(PartialEq, Eq, Hash)]
-enum Compartment {
-    Left,
-    Right,
+const COMPARTMENTS: usize = 2;
+const GROUP_SIZE: usize = 3;
+// Policy used for the puzzle's answers
+const TIE_BREAK: TieBreak = TieBreak::Lowest;
+
+#[derive(Debug)]
+enum RucksackError {
+    UnevenCompartments {
+        sack: String,
+        compartments: usize,
+    },
+    EmptyGroupSize,
+    IncompleteGroup {
+        group: usize,
+        size: usize,
+        expected: usize,
+    },
+    UnknownItem {
+        sack: String,
+        item: char,
+    },
+    InvalidScheme {
+        line: usize,
+        reason: String,
+    },
+    SchemeTooLarge(usize),
+    SchemeUnreadable(String),
 }
 
-// Gets the priority of a packed item represented by a char
-fn get_item_priority(item: char) -> usize {
-    match item {
-        'a'..='z' => item as usize - BASE_PRIORITY_LOWER + 1,
-        'A'..='Z' => item as usize - BASE_PRIORITY_UPPER + 27,
-        _ => 0,
+impl fmt::Display for RucksackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RucksackError::UnevenCompartments { sack, compartments } => write!(
+                f,
+                "Rucksack {:?} has {} items, which cannot be split into {} compartments",
+                sack,
+                sack.chars().count(),
+                compartments
+            ),
+            RucksackError::EmptyGroupSize => write!(f, "Group size must be at least 1"),
+            RucksackError::IncompleteGroup {
+                group,
+                size,
+                expected,
+            } => write!(
+                f,
+                "Group {} has {} rucksacks, expected {}",
+                group, size, expected
+            ),
+            RucksackError::UnknownItem { sack, item } => write!(
+                f,
+                "Rucksack {:?} contains {:?}, which has no priority in this scheme",
+                sack, item
+            ),
+            RucksackError::InvalidScheme { line, reason } => {
+                write!(f, "Invalid priority scheme on line {}: {}", line, reason)
+            }
+            RucksackError::SchemeTooLarge(size) => write!(
+                f,
+                "Priority scheme has {} items, but at most {} are supported",
+                size,
+                ItemSet::CAPACITY
+            ),
+            RucksackError::SchemeUnreadable(reason) => {
+                write!(f, "Unable to read priority scheme: {}", reason)
+            }
+        }
     }
 }
 
-struct RucksackCompartment {
-    _set: HashSet<char>,
-    items: Vec<char>,
+/**
+ * Maps items to priorities. Each item is also assigned a slot, its position
+ * when sorted by priority, which is used as its bit in an `ItemSet`.
+ */
+struct PriorityScheme {
+    items: Vec<(char, usize)>,
+    ascii_slots: [Option<usize>; 128],
+    other_slots: HashMap<char, usize>,
 }
 
-impl RucksackCompartment {
-    fn new() -> RucksackCompartment {
-        RucksackCompartment {
-            _set: HashSet::new(),
-            items: vec![],
+impl PriorityScheme {
+    // Creates a scheme from item/priority pairs
+    fn new(mut items: Vec<(char, usize)>) -> Result<PriorityScheme, RucksackError> {
+        if items.len() > ItemSet::CAPACITY {
+            return Err(RucksackError::SchemeTooLarge(items.len()));
+        }
+
+        items.sort_by_key(|&(item, priority)| (priority, item));
+
+        // ASCII items are looked up by index, as they make up nearly all input
+        let mut ascii_slots = [None; 128];
+        let mut other_slots = HashMap::new();
+        for (slot, &(item, _)) in items.iter().enumerate() {
+            match ascii_slots.get_mut(item as usize) {
+                Some(entry) => *entry = Some(slot),
+                None => {
+                    other_slots.insert(item, slot);
+                }
+            }
         }
+
+        Ok(PriorityScheme {
+            items,
+            ascii_slots,
+            other_slots,
+        })
+    }
+
+    // Loads a scheme from a file, one whitespace-separated item and priority per line
+    fn from_file(path: impl AsRef<Path>) -> Result<PriorityScheme, RucksackError> {
+        let raw = fs::read_to_string(&path).map_err(|e| {
+            RucksackError::SchemeUnreadable(format!("{}: {}", path.as_ref().display(), e))
+        })?;
+        raw.parse()
+    }
+
+    // Gets the slot of an item, if it has a priority
+    fn slot(&self, item: char) -> Option<usize> {
+        match self.ascii_slots.get(item as usize) {
+            Some(slot) => *slot,
+            None => self.other_slots.get(&item).copied(),
+        }
+    }
+
+    // Gets the item stored in a slot
+    fn item(&self, slot: usize) -> char {
+        self.items[slot].0
+    }
+
+    // Gets the priority of the item stored in a slot
+    fn priority(&self, slot: usize) -> usize {
+        self.items[slot].1
     }
 
-    fn add_item(&mut self, item: char) {
-        self._set.insert(item);
-        self.items.push(item);
+    // Gets the priority of a packed item represented by a char
+    fn item_priority(&self, item: char) -> Option<usize> {
+        self.slot(item).map(|slot| self.priority(slot))
+    }
+
+    // Gets the items in a set, in ascending priority order
+    fn items_in(&self, set: &ItemSet) -> String {
+        set.iter().map(|slot| self.item(slot)).collect()
     }
 }
 
-struct Rucksack {
-    _set: HashSet<char>,
-    compartments: HashMap<Compartment, RucksackCompartment>,
+// Default scheme: a-z are worth 1-26, and A-Z are worth 27-52
+impl Default for PriorityScheme {
+    fn default() -> Self {
+        let lower = ('a'..='z').map(|c| (c, c as usize - BASE_PRIORITY_LOWER + 1));
+        let upper = ('A'..='Z').map(|c| (c, c as usize - BASE_PRIORITY_UPPER + 27));
+        PriorityScheme::new(lower.chain(upper).collect()).unwrap()
+    }
 }
 
-impl FromStr for Rucksack {
+impl FromStr for PriorityScheme {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let len = s.len();
-        let items = s.chars();
-
-        let mut sack = Rucksack::new();
-        for (index, item) in items.enumerate() {
-            let target = if index < len / 2 {
-                Compartment::Left
-            } else {
-                Compartment::Right
+        let mut items = vec![];
+        let mut seen = HashSet::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let invalid = |reason: &str| RucksackError::InvalidScheme {
+                line: index + 1,
+                reason: reason.to_string(),
             };
-            sack.add_to_compartment(target, item);
+
+            let mut parts = line.split_ascii_whitespace();
+            let (item, priority) = match (parts.next(), parts.next(), parts.next()) {
+                (None, _, _) => continue,
+                (Some(item), Some(priority), None) => (item, priority),
+                _ => return Err(invalid("expected an item and a priority")),
+            };
+
+            let mut chars = item.chars();
+            let item = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(invalid("items must be a single character")),
+            };
+            let priority = priority
+                .parse::<usize>()
+                .map_err(|_| invalid("priority must be a non-negative integer"))?;
+
+            if !seen.insert(item) {
+                return Err(invalid("item is listed more than once"));
+            }
+            items.push((item, priority));
         }
 
-        Ok(sack)
+        PriorityScheme::new(items)
+    }
+    type Err = RucksackError;
+}
+
+/**
+ * Policy for choosing a single item when several are shared
+ */
+#[derive(Clone, Copy, Debug)]
+enum TieBreak {
+    Lowest,
+    Highest,
+}
+
+impl FromStr for TieBreak {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lowest" => Ok(TieBreak::Lowest),
+            "highest" => Ok(TieBreak::Highest),
+            _ => Err(format!(
+                "Unknown tie-break {:?}, expected lowest or highest",
+                s
+            )),
+        }
     }
-    type Err = ParseError;
+    type Err = String;
+}
+
+/**
+ * Set of packed items, stored as a bitmask where bit N is set if the item in
+ * slot N of the priority scheme is present
+ */
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct ItemSet(u64);
+
+impl ItemSet {
+    const CAPACITY: usize = 64;
+
+    // Creates a set containing every possible item
+    fn full() -> ItemSet {
+        ItemSet(u64::MAX)
+    }
+
+    // Adds an item to the set
+    fn insert(&mut self, slot: usize) {
+        self.0 |= 1 << slot;
+    }
+
+    // Checks if an item exists in the set
+    fn contains(&self, slot: usize) -> bool {
+        self.0 & (1 << slot) != 0
+    }
+
+    // Gets the items present in both sets
+    fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    // Gets the number of items in the set
+    fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    // Gets the lowest priority item in the set
+    fn first(&self) -> Option<usize> {
+        match self.0 {
+            0 => None,
+            bits => Some(bits.trailing_zeros() as usize),
+        }
+    }
+
+    // Gets the highest priority item in the set
+    fn last(&self) -> Option<usize> {
+        match self.0 {
+            0 => None,
+            bits => Some(63 - bits.leading_zeros() as usize),
+        }
+    }
+
+    // Picks a single item from the set according to a tie-break policy
+    fn pick(&self, tie_break: TieBreak) -> Option<usize> {
+        match tie_break {
+            TieBreak::Lowest => self.first(),
+            TieBreak::Highest => self.last(),
+        }
+    }
+
+    // Iterates items in the set in ascending priority order
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..ItemSet::CAPACITY).filter(|&slot| self.contains(slot))
+    }
+}
+
+struct Rucksack {
+    items: ItemSet,
+    compartments: Vec<ItemSet>,
 }
 
 impl Rucksack {
-    // Creates a new, empty rucksack
-    fn new() -> Rucksack {
-        Rucksack {
-            _set: HashSet::new(),
-            compartments: HashMap::new(),
+    // Creates a rucksack from a line of items, split evenly into compartments
+    fn new(
+        s: &str,
+        compartments: usize,
+        scheme: &PriorityScheme,
+    ) -> Result<Rucksack, RucksackError> {
+        let items: Vec<char> = s.chars().collect();
+        if compartments == 0 || !items.len().is_multiple_of(compartments) {
+            return Err(RucksackError::UnevenCompartments {
+                sack: s.to_string(),
+                compartments,
+            });
         }
+
+        let size = items.len() / compartments;
+        let mut sack = Rucksack {
+            items: ItemSet::default(),
+            compartments: vec![ItemSet::default(); compartments],
+        };
+        for (index, item) in items.into_iter().enumerate() {
+            let slot = scheme
+                .slot(item)
+                .ok_or_else(|| RucksackError::UnknownItem {
+                    sack: s.to_string(),
+                    item,
+                })?;
+            sack.add_to_compartment(index / size, slot);
+        }
+
+        Ok(sack)
     }
 
     // Adds an item to a compartment of the rucksack
-    fn add_to_compartment(&mut self, compartment: Compartment, item: char) {
-        self._set.insert(item);
-        self.compartments
-            .entry(compartment)
-            .and_modify(|e| e.add_item(item))
-            .or_insert({
-                let mut c = RucksackCompartment::new();
-                c.add_item(item);
-                c
-            });
+    fn add_to_compartment(&mut self, compartment: usize, slot: usize) {
+        self.items.insert(slot);
+        self.compartments[compartment].insert(slot);
     }
 
     // Checks if an item exists in a specific compartment of the rucksack
-    fn exists_in_compartment(&self, compartment: Compartment, item: &char) -> bool {
-        match self.compartments.get(&compartment) {
-            Some(c) => c._set.contains(item),
-            None => false,
-        }
+    fn exists_in_compartment(&self, compartment: usize, slot: usize) -> bool {
+        self.compartments
+            .get(compartment)
+            .is_some_and(|c| c.contains(slot))
     }
 
     // Checks if an item exists in the rucksack
-    fn exists(&self, item: &char) -> bool {
-        self._set.contains(item)
+    fn exists(&self, slot: usize) -> bool {
+        self.items.contains(slot)
     }
 
-    // Allows iterating all items in the rucksack
-    fn items(&self) -> Iter<char> {
-        self._set.iter()
+    // Gets the set of all items in the rucksack
+    fn items(&self) -> ItemSet {
+        self.items
     }
 }
 
+fn parse_rucksacks(
+    lines: &[String],
+    compartments: usize,
+    scheme: &PriorityScheme,
+) -> Result<Vec<Rucksack>, RucksackError> {
+    lines
+        .iter()
+        .map(|line| Rucksack::new(line, compartments, scheme))
+        .collect()
+}
+
 // PART 1
-// Find and sum values of items within each sack that are in both compartments
+// Find and sum values of items within each sack that are in every compartment
 
-fn find_duplicate_item(sack: &Rucksack) -> Option<char> {
-    for item in sack.items() {
-        if sack.exists_in_compartment(Compartment::Left, item)
-            && sack.exists_in_compartment(Compartment::Right, item)
-        {
-            return Some(*item);
-        }
-    }
-    None
+fn find_duplicate_items(sack: &Rucksack) -> ItemSet {
+    sack.compartments
+        .iter()
+        .fold(ItemSet::full(), |shared, c| shared.intersection(c))
+}
+
+fn find_duplicate_item(
+    sack: &Rucksack,
+    scheme: &PriorityScheme,
+    tie_break: TieBreak,
+) -> Option<char> {
+    find_duplicate_items(sack)
+        .pick(tie_break)
+        .map(|slot| scheme.item(slot))
 }
 
-fn sum_duplicate_item_priorities(sacks: &[Rucksack]) -> usize {
+fn sum_duplicate_item_priorities(
+    sacks: &[Rucksack],
+    scheme: &PriorityScheme,
+    tie_break: TieBreak,
+) -> usize {
     sacks
         .iter()
-        .map(|s| find_duplicate_item(s).map_or(0, get_item_priority))
+        .filter_map(|s| find_duplicate_items(s).pick(tie_break))
+        .map(|slot| scheme.priority(slot))
         .sum()
 }
 
+fn solve_part1(
+    lines: &[String],
+    compartments: usize,
+    scheme: &PriorityScheme,
+    tie_break: TieBreak,
+) -> Result<usize, RucksackError> {
+    let sacks = parse_rucksacks(lines, compartments, scheme)?;
+    Ok(sum_duplicate_item_priorities(&sacks, scheme, tie_break))
+}
+
 pub fn part1() {
     print::intro(3, 1);
 
-    let sacks = input::day_input::<Rucksack>(3);
-    let sum_test = sum_duplicate_item_priorities(&sacks.test);
-    let sum_real = sum_duplicate_item_priorities(&sacks.real);
+    let data = input::day_input::<String>(3);
+    let scheme = PriorityScheme::default();
+    let sum_test = solve_part1(&data.test, COMPARTMENTS, &scheme, TIE_BREAK).unwrap();
+    let sum_real = solve_part1(&data.real, COMPARTMENTS, &scheme, TIE_BREAK).unwrap();
 
     print::answer_with_test(sum_real, sum_test);
 }
 
 // PART 2
-// Find the shared item in each group of three rucksacks
-
-fn find_badge(group: &[Rucksack]) -> Option<char> {
-    match group.split_first() {
-        Some((first, rest)) => {
-            'item: for item in first.items() {
-                for other in rest {
-                    if !other.exists(item) {
-                        continue 'item;
-                    }
-                }
-                return Some(*item);
-            }
-            None
+// Find the shared item in each group of rucksacks
+
+fn find_badges(group: &[Rucksack]) -> ItemSet {
+    if group.is_empty() {
+        return ItemSet::default();
+    }
+    group.iter().fold(ItemSet::full(), |shared, sack| {
+        shared.intersection(&sack.items())
+    })
+}
+
+fn find_badge(group: &[Rucksack], scheme: &PriorityScheme, tie_break: TieBreak) -> Option<char> {
+    find_badges(group)
+        .pick(tie_break)
+        .map(|slot| scheme.item(slot))
+}
+
+fn sum_badge_priorities(
+    sacks: &[Rucksack],
+    group_size: usize,
+    scheme: &PriorityScheme,
+    tie_break: TieBreak,
+) -> Result<usize, RucksackError> {
+    if group_size == 0 {
+        return Err(RucksackError::EmptyGroupSize);
+    }
+
+    let mut sum = 0;
+    for (index, group) in sacks.chunks(group_size).enumerate() {
+        if group.len() < group_size {
+            return Err(RucksackError::IncompleteGroup {
+                group: index + 1,
+                size: group.len(),
+                expected: group_size,
+            });
         }
-        None => None,
+        sum += find_badges(group)
+            .pick(tie_break)
+            .map_or(0, |slot| scheme.priority(slot));
     }
+    Ok(sum)
 }
 
-fn sum_badge_priorities(sacks: &[Rucksack]) -> usize {
-    sacks
-        .chunks(3)
-        .map(|group| find_badge(group).map_or(0, get_item_priority))
-        .sum()
+fn solve_part2(
+    lines: &[String],
+    compartments: usize,
+    group_size: usize,
+    scheme: &PriorityScheme,
+    tie_break: TieBreak,
+) -> Result<usize, RucksackError> {
+    let sacks = parse_rucksacks(lines, compartments, scheme)?;
+    sum_badge_priorities(&sacks, group_size, scheme, tie_break)
 }
 
 pub fn part2() {
     print::intro(3, 2);
 
-    let sacks = input::day_input::<Rucksack>(3);
-    let sum_test = sum_badge_priorities(&sacks.test);
-    let sum_real = sum_badge_priorities(&sacks.real);
+    let data = input::day_input::<String>(3);
+    let scheme = PriorityScheme::default();
+    let sum_test = solve_part2(&data.test, COMPARTMENTS, GROUP_SIZE, &scheme, TIE_BREAK).unwrap();
+    let sum_real = solve_part2(&data.real, COMPARTMENTS, GROUP_SIZE, &scheme, TIE_BREAK).unwrap();
 
     print::answer_with_test(sum_real, sum_test);
 }
+
+// CUSTOM SCHEME
+// Solve both parts using a priority table loaded from a file
+
+pub fn with_scheme(path: &str) {
+    println!("=== DAY 3, SCHEME {} ===", path);
+
+    let data = input::day_input::<String>(3);
+    let scheme = match PriorityScheme::from_file(path) {
+        Ok(scheme) => scheme,
+        Err(e) => return println!("{}\n", e),
+    };
+
+    for (name, lines) in [("Test", &data.test), ("Real", &data.real)] {
+        let part1 = solve_part1(lines, COMPARTMENTS, &scheme, TIE_BREAK);
+        let part2 = solve_part2(lines, COMPARTMENTS, GROUP_SIZE, &scheme, TIE_BREAK);
+        match (part1, part2) {
+            (Ok(part1), Ok(part2)) => println!("{}: {} / {}", name, part1, part2),
+            (Err(e), _) | (_, Err(e)) => println!("{}: {}", name, e),
+        }
+    }
+    println!();
+}
+
+// DIAGNOSTICS
+// List sacks and groups that don't share exactly one item
+
+struct Anomaly {
+    label: String,
+    shared: String,
+    picked: Option<char>,
+}
+
+impl Anomaly {
+    fn new(
+        label: String,
+        shared: &ItemSet,
+        scheme: &PriorityScheme,
+        tie_break: TieBreak,
+    ) -> Anomaly {
+        Anomaly {
+            label,
+            shared: scheme.items_in(shared),
+            picked: shared.pick(tie_break).map(|slot| scheme.item(slot)),
+        }
+    }
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.picked {
+            None => write!(f, "{}: no shared items", self.label),
+            Some(picked) => write!(
+                f,
+                "{}: {} shared items ({}), picked {}",
+                self.label,
+                self.shared.chars().count(),
+                self.shared,
+                picked
+            ),
+        }
+    }
+}
+
+struct DiagnosticsReport {
+    tie_break: TieBreak,
+    sacks: Vec<Anomaly>,
+    groups: Vec<Anomaly>,
+}
+
+impl DiagnosticsReport {
+    fn new(
+        sacks: &[Rucksack],
+        group_size: usize,
+        scheme: &PriorityScheme,
+        tie_break: TieBreak,
+    ) -> DiagnosticsReport {
+        let sack_anomalies = sacks
+            .iter()
+            .enumerate()
+            .map(|(index, sack)| (index, find_duplicate_items(sack)))
+            .filter(|(_, shared)| shared.len() != 1)
+            .map(|(index, shared)| {
+                Anomaly::new(format!("Sack {}", index + 1), &shared, scheme, tie_break)
+            })
+            .collect();
+
+        let group_anomalies = sacks
+            .chunks(group_size.max(1))
+            .enumerate()
+            .map(|(index, group)| (index, find_badges(group)))
+            .filter(|(_, shared)| shared.len() != 1)
+            .map(|(index, shared)| {
+                Anomaly::new(format!("Group {}", index + 1), &shared, scheme, tie_break)
+            })
+            .collect();
+
+        DiagnosticsReport {
+            tie_break,
+            sacks: sack_anomalies,
+            groups: group_anomalies,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Tie-break: {:?} priority", self.tie_break)?;
+        writeln!(
+            f,
+            "Sacks without exactly one duplicate: {}",
+            self.sacks.len()
+        )?;
+        for anomaly in &self.sacks {
+            writeln!(f, "  {}", anomaly)?;
+        }
+        write!(f, "Groups without exactly one badge: {}", self.groups.len())?;
+        for anomaly in &self.groups {
+            write!(f, "\n  {}", anomaly)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn diagnostics(tie_break: &str) {
+    println!("=== DAY 3, DIAGNOSTICS ===");
+
+    let tie_break: TieBreak = match tie_break.parse() {
+        Ok(tie_break) => tie_break,
+        Err(e) => return println!("{}\n", e),
+    };
+    let data = input::day_input::<String>(3);
+    let scheme = PriorityScheme::default();
+    let sacks_test = parse_rucksacks(&data.test, COMPARTMENTS, &scheme).unwrap();
+    let sacks_real = parse_rucksacks(&data.real, COMPARTMENTS, &scheme).unwrap();
+
+    print::answer_with_test_newline(
+        DiagnosticsReport::new(&sacks_real, GROUP_SIZE, &scheme, tie_break),
+        DiagnosticsReport::new(&sacks_test, GROUP_SIZE, &scheme, tie_break),
+    );
+}
+
+// BENCHMARK
+// Compare the bitset rucksacks against the previous HashSet-based approach
+
+fn solve_with_hash_sets(lines: &[String]) -> (usize, usize) {
+    let scheme = PriorityScheme::default();
+    let get_item_priority = |item: &char| scheme.item_priority(*item).unwrap_or(0);
+
+    let sets: Vec<(HashSet<char>, HashSet<char>, HashSet<char>)> = lines
+        .iter()
+        .map(|line| {
+            let (left, right) = line.split_at(line.len() / 2);
+            (
+                left.chars().collect(),
+                right.chars().collect(),
+                line.chars().collect(),
+            )
+        })
+        .collect();
+
+    let duplicates = sets
+        .iter()
+        .map(|(left, right, _)| left.intersection(right).next().map_or(0, get_item_priority))
+        .sum();
+
+    let badges = sets
+        .chunks(3)
+        .map(|group| {
+            let (first, rest) = group.split_first().unwrap();
+            first
+                .2
+                .iter()
+                .find(|item| rest.iter().all(|(_, _, other)| other.contains(item)))
+                .map_or(0, get_item_priority)
+        })
+        .sum();
+
+    (duplicates, badges)
+}
+
+fn solve_with_bitsets(lines: &[String]) -> (usize, usize) {
+    let scheme = PriorityScheme::default();
+    let sacks = parse_rucksacks(lines, COMPARTMENTS, &scheme).unwrap();
+    (
+        sum_duplicate_item_priorities(&sacks, &scheme, TIE_BREAK),
+        sum_badge_priorities(&sacks, GROUP_SIZE, &scheme, TIE_BREAK).unwrap(),
+    )
+}
+
+pub fn bench() {
+    const ITERATIONS: u32 = 1000;
+
+    println!("=== DAY 3, BENCHMARK ===");
+    let data = input::day_input::<String>(3);
+
+    let time = |solve: &dyn Fn(&[String]) -> (usize, usize)| {
+        let start = Instant::now();
+        let mut result = (0, 0);
+        for _ in 0..ITERATIONS {
+            result = solve(&data.real);
+        }
+        (result, start.elapsed() / ITERATIONS)
+    };
+
+    let (result_hash, elapsed_hash) = time(&solve_with_hash_sets);
+    let (result_bits, elapsed_bits) = time(&solve_with_bitsets);
+    assert_eq!(result_hash, result_bits, "Bitset results differ");
+
+    println!("HashSet: {:?} per run", elapsed_hash);
+    println!("Bitset:  {:?} per run", elapsed_bits);
+    println!(
+        "Speedup: {:.1}x\n",
+        elapsed_hash.as_secs_f64() / elapsed_bits.as_secs_f64()
+    );
+}
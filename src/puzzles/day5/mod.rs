@@ -1,12 +1,15 @@
 use regex::Regex;
-use std::cmp;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
 use std::str::FromStr;
-use std::string::ParseError;
+use std::thread;
+use std::time::Duration;
 
 use crate::input;
 use crate::print;
 
-type Crate = char;
+type Crate = String;
 type Stack = Vec<Crate>;
 
 #[derive(Debug)]
@@ -18,16 +21,354 @@ struct Instruction {
 
 impl FromStr for Instruction {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let regex = Regex::new(r"\d+").unwrap();
+        let regex = Regex::new(r"^move (\S+) from (\S+) to (\S+)$").unwrap();
+        let caps = regex
+            .captures(s.trim())
+            .ok_or_else(|| format!("expected \"move N from A to B\", found {:?}", s))?;
+
+        let field = |index: usize, name: &str| {
+            caps[index]
+                .parse::<usize>()
+                .map_err(|_| format!("invalid {} {:?} in {:?}", name, &caps[index], s))
+        };
+        let repeat = field(1, "crate count")?;
+        let from = field(2, "source stack")?;
+        let to = field(3, "target stack")?;
+
+        // Stacks are numbered from 1
+        if from == 0 || to == 0 {
+            return Err(format!("stack 0 does not exist in {:?}", s));
+        }
+
+        Ok(Instruction {
+            from: from - 1,
+            to: to - 1,
+            repeat,
+        })
+    }
+    type Err = String;
+}
+
+/**
+ * Problems with crane instructions. Instructions and stacks are numbered
+ * from 1, as in the puzzle input.
+ */
+#[derive(Debug, Clone)]
+enum CraneError {
+    InvalidInstruction {
+        instruction: usize,
+        reason: String,
+    },
+    MissingStack {
+        instruction: usize,
+        stack: usize,
+        stacks: usize,
+    },
+    NotEnoughCrates {
+        instruction: usize,
+        stack: usize,
+        requested: usize,
+        available: usize,
+    },
+}
+
+impl fmt::Display for CraneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CraneError::InvalidInstruction {
+                instruction,
+                reason,
+            } => write!(f, "Instruction {}: {}", instruction, reason),
+            CraneError::MissingStack {
+                instruction,
+                stack,
+                stacks,
+            } => write!(
+                f,
+                "Instruction {}: stack {} does not exist (ship has {} stacks)",
+                instruction, stack, stacks
+            ),
+            CraneError::NotEnoughCrates {
+                instruction,
+                stack,
+                requested,
+                available,
+            } => write!(
+                f,
+                "Instruction {}: stack {} is {} short (requested {}, has {})",
+                instruction,
+                stack,
+                crates(requested - available),
+                requested,
+                available
+            ),
+        }
+    }
+}
+
+// An instruction line, or why it couldn't be parsed. Parse errors are kept
+// per line so they can be skipped like any other invalid instruction.
+type ParsedInstruction = Result<Instruction, CraneError>;
+
+// A number of crates, e.g. "1 crate" or "3 crates"
+fn crates(count: usize) -> String {
+    match count {
+        1 => String::from("1 crate"),
+        _ => format!("{} crates", count),
+    }
+}
+
+// What to do when an instruction can't be carried out
+#[derive(Debug, Clone, Copy)]
+enum OnError {
+    Abort,
+    Skip,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.repeat,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+/**
+ * Where things were drawn in the diagram a ship was parsed from, as character
+ * columns: the start of every crate, from the bottom of each stack up, and the
+ * start of each stack number in the footer
+ */
+#[derive(Clone)]
+struct Layout {
+    crates: Vec<Vec<usize>>,
+    labels: Vec<usize>,
+    width: usize,
+}
+
+impl Layout {
+    // Where a crate at a given height of a stack starts. Heights the diagram
+    // never had use the stack's bottom crate, or sit over the stack number.
+    fn crate_column(&self, stack: usize, row: usize) -> usize {
+        let starts = &self.crates[stack];
+        match starts.get(row).or(starts.first()) {
+            Some(&column) => column,
+            None => self.labels[stack].saturating_sub(1),
+        }
+    }
+}
+
+// Renders stacks in the puzzle's diagram format, with a numbered footer,
+// drawing everything in the columns of the original diagram. A crate too wide
+// for its column is pushed along to leave a space after its neighbour. Padded
+// diagrams keep trailing spaces so every line is as wide as the original.
+fn render_stacks(stacks: &[Stack], layout: &Layout, padded: bool) -> String {
+    let draw = |pieces: Vec<(usize, String)>| {
+        let mut line = String::new();
+        for (column, text) in pieces {
+            let len = line.chars().count();
+            let column = if len == 0 {
+                column
+            } else {
+                column.max(len + 1)
+            };
+            line += &" ".repeat(column - len);
+            line += &text;
+        }
+        if padded {
+            let len = line.chars().count();
+            line + &" ".repeat(layout.width.saturating_sub(len))
+        } else {
+            line.trim_end().to_string()
+        }
+    };
+
+    let height = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+    let mut lines = vec![];
+
+    for row in (0..height).rev() {
+        let pieces = stacks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, stack)| {
+                let c = stack.get(row)?;
+                Some((layout.crate_column(i, row), format!("[{}]", c)))
+            })
+            .collect();
+        lines.push(draw(pieces));
+    }
+
+    let footer = (0..stacks.len())
+        .map(|i| (layout.labels[i], (i + 1).to_string()))
+        .collect();
+    lines.push(draw(footer));
+
+    lines.join("\n")
+}
+
+// Moves the top crates of one stack onto another, keeping their order
+fn move_crates(stacks: &mut [Stack], from: usize, to: usize, items: usize) {
+    // Pop substack off source stack
+    let stack_from = &mut stacks[from];
+    let mut substack = stack_from.split_off(stack_from.len() - items);
+
+    // Append substack to target stack
+    stacks[to].append(&mut substack);
+}
+
+/**
+ * A crane model, which decides how the crates in an instruction are moved.
+ * Instructions are validated before being passed to a crane, so the source
+ * stack always holds enough crates.
+ */
+trait Crane {
+    fn name(&self) -> String;
+
+    // Carries out an instruction, returning the number of lifts it took
+    fn apply(&self, stacks: &mut [Stack], instruction: &Instruction) -> usize;
+
+    // Reverses `apply`, returning the crates to where they came from. The
+    // target stack always holds enough crates.
+    fn undo(&self, stacks: &mut [Stack], instruction: &Instruction);
+}
+
+/**
+ * CrateMover 9000: lifts one crate at a time
+ */
+struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9000")
+    }
+
+    fn apply(&self, stacks: &mut [Stack], instruction: &Instruction) -> usize {
+        for _ in 0..instruction.repeat {
+            move_crates(stacks, instruction.from, instruction.to, 1);
+        }
+        instruction.repeat
+    }
+
+    fn undo(&self, stacks: &mut [Stack], instruction: &Instruction) {
+        for _ in 0..instruction.repeat {
+            move_crates(stacks, instruction.to, instruction.from, 1);
+        }
+    }
+}
+
+/**
+ * CrateMover 9001: lifts every crate in an instruction at once
+ */
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9001")
+    }
+
+    fn apply(&self, stacks: &mut [Stack], instruction: &Instruction) -> usize {
+        move_crates(stacks, instruction.from, instruction.to, instruction.repeat);
+        // Moving no crates takes no lifts
+        usize::from(instruction.repeat > 0)
+    }
+
+    fn undo(&self, stacks: &mut [Stack], instruction: &Instruction) {
+        move_crates(stacks, instruction.to, instruction.from, instruction.repeat);
+    }
+}
+
+/**
+ * Lifts whole substacks like the 9001, but no more than `max` crates at a
+ * time, splitting larger moves into several lifts
+ */
+struct MaxLiftCrane {
+    max: usize,
+}
 
-        let mut parts = regex.find_iter(s).map(|m| m.as_str().parse::<usize>());
-        let repeat = parts.next().unwrap().expect("Invalid instruction");
-        let from = parts.next().unwrap().expect("Invalid instruction") - 1;
-        let to = parts.next().unwrap().expect("Invalid instruction") - 1;
+impl Crane for MaxLiftCrane {
+    fn name(&self) -> String {
+        format!("Max lift {} crane", self.max)
+    }
 
-        Ok(Instruction { from, to, repeat })
+    fn apply(&self, stacks: &mut [Stack], instruction: &Instruction) -> usize {
+        let mut remaining = instruction.repeat;
+        let mut lifts = 0;
+        while remaining > 0 {
+            let items = remaining.min(self.max);
+            move_crates(stacks, instruction.from, instruction.to, items);
+            remaining -= items;
+            lifts += 1;
+        }
+        lifts
+    }
+
+    fn undo(&self, stacks: &mut [Stack], instruction: &Instruction) {
+        // Lifts were full-sized apart from the last, so undo that one first
+        let mut remaining = instruction.repeat;
+        let mut items = match remaining % self.max {
+            0 => self.max,
+            partial => partial,
+        };
+        while remaining > 0 {
+            move_crates(stacks, instruction.to, instruction.from, items);
+            remaining -= items;
+            items = self.max;
+        }
     }
-    type Err = ParseError;
+}
+
+/**
+ * Pulls crates out from the bottom of the source stack, keeping their order,
+ * and places them on top of the target stack
+ */
+struct BottomCrane;
+
+impl Crane for BottomCrane {
+    fn name(&self) -> String {
+        String::from("Bottom crane")
+    }
+
+    fn apply(&self, stacks: &mut [Stack], instruction: &Instruction) -> usize {
+        let substack: Vec<Crate> = stacks[instruction.from]
+            .drain(..instruction.repeat)
+            .collect();
+        stacks[instruction.to].extend(substack);
+        usize::from(instruction.repeat > 0)
+    }
+
+    fn undo(&self, stacks: &mut [Stack], instruction: &Instruction) {
+        let target = &mut stacks[instruction.to];
+        let substack = target.split_off(target.len() - instruction.repeat);
+        stacks[instruction.from].splice(0..0, substack);
+    }
+}
+
+// Picks a crane by name: "9000" (or "1"), "9001" (or "2"), "bottom", or
+// "max:N" for a crane lifting at most N crates at a time
+fn parse_crane(name: &str) -> Result<Box<dyn Crane>, String> {
+    match name {
+        "9000" | "1" => Ok(Box::new(CrateMover9000)),
+        "9001" | "2" => Ok(Box::new(CrateMover9001)),
+        "bottom" => Ok(Box::new(BottomCrane)),
+        _ => match name.strip_prefix("max:").map(|max| max.parse::<usize>()) {
+            Some(Ok(max)) if max > 0 => Ok(Box::new(MaxLiftCrane { max })),
+            _ => Err(format!("Unknown crane {:?}", name)),
+        },
+    }
+}
+
+// Column of a byte offset in a line, counted in characters so multi-byte
+// labels don't shift later columns
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count()
+}
+
+// Column of the middle character in a byte range of a line
+fn centre(line: &str, start: usize, end: usize) -> usize {
+    column(line, start) + line[start..end].chars().count() / 2
 }
 
 /**
@@ -36,111 +377,399 @@ impl FromStr for Instruction {
  */
 struct Ship {
     stacks: Vec<Stack>,
-    is_crane_version_9001: bool,
+    crane: Box<dyn Crane>,
+    layout: Layout,
+    padded: bool,
+    costs: Vec<InstructionCost>,
 }
 
 impl Ship {
-    pub fn new(initial_state: &[String], is_crane_version_9001: bool) -> Ship {
+    pub fn new(initial_state: &[String], crane: Box<dyn Crane>) -> Ship {
         let mut diagram = initial_state.iter().rev();
+        let footer = diagram.next().unwrap();
+
+        // Each stack number in the footer marks the centre of its column
+        let labels: Vec<_> = Regex::new(r"\S+").unwrap().find_iter(footer).collect();
+        let centres: Vec<usize> = labels
+            .iter()
+            .map(|m| centre(footer, m.start(), m.end()))
+            .collect();
+        let mut stacks = vec![Stack::new(); centres.len()];
+        let mut layout = Layout {
+            crates: vec![vec![]; centres.len()],
+            labels: labels.iter().map(|m| column(footer, m.start())).collect(),
+            width: footer.chars().count(),
+        };
 
-        // Get number of stacks on ship
-        let num_stacks = diagram
-            .next()
-            .unwrap()
-            .split_ascii_whitespace()
-            .last()
-            .unwrap()
-            .parse::<usize>()
-            .expect("Invalid input");
-
-        // Create stacks
-        let mut stacks = vec![];
-        for _ in 0..num_stacks {
-            stacks.push(Stack::new());
-        }
-
-        // Add crates to stacks
+        // Add each crate to the stack whose column it sits closest to
+        let crate_regex = Regex::new(r"\[([^\]]+)\]").unwrap();
         for line in diagram {
-            let chars = line.as_bytes();
-            for i in 0..chars.len() {
-                if let (Some(stack), Some(c)) = (stacks.get_mut(i), chars.get(1 + i * 4)) {
-                    if *c != b' ' {
-                        stack.push(*c as char);
-                    }
-                }
+            for caps in crate_regex.captures_iter(line) {
+                let outer = caps.get(0).unwrap();
+                let position = centre(line, outer.start(), outer.end());
+                let stack = (0..centres.len())
+                    .min_by_key(|&i| centres[i].abs_diff(position))
+                    .expect("Invalid input");
+                stacks[stack].push(caps[1].to_string());
+                layout.crates[stack].push(column(line, outer.start()));
             }
         }
 
         Ship {
             stacks,
-            is_crane_version_9001,
+            crane,
+            layout,
+            padded: footer.ends_with(' '),
+            costs: vec![],
         }
     }
 
-    fn move_crates(&mut self, from: usize, to: usize, items: usize) {
-        // Ensure source and target stacks exist
-        if self.stacks.len() - 1 < cmp::max(from, to) {
-            return;
+    // Checks an instruction can be carried out in full before any crates move.
+    // Crates are taken from `source`, which is the target stack when undoing.
+    fn validate(
+        &self,
+        number: usize,
+        instruction: &Instruction,
+        source: usize,
+    ) -> Result<(), CraneError> {
+        for stack in [instruction.from, instruction.to] {
+            if stack >= self.stacks.len() {
+                return Err(CraneError::MissingStack {
+                    instruction: number,
+                    stack: stack + 1,
+                    stacks: self.stacks.len(),
+                });
+            }
         }
 
-        // Pop substack off source stack
-        let mut substack = match self.stacks.get_mut(from) {
-            Some(stack_from) => stack_from.split_off(stack_from.len() - items),
-            None => vec![],
-        };
+        let available = self.stacks[source].len();
+        if available < instruction.repeat {
+            return Err(CraneError::NotEnoughCrates {
+                instruction: number,
+                stack: source + 1,
+                requested: instruction.repeat,
+                available,
+            });
+        }
+
+        Ok(())
+    }
 
-        // Append substack to target stack
-        if let Some(target) = self.stacks.get_mut(to) {
-            target.append(&mut substack);
+    // Validates then applies a single instruction, numbered from 1
+    pub fn apply_instruction(
+        &mut self,
+        number: usize,
+        instruction: &Instruction,
+    ) -> Result<(), CraneError> {
+        self.validate(number, instruction, instruction.from)?;
+        let was_empty: Vec<bool> = self.stacks.iter().map(|s| s.is_empty()).collect();
+        let lifts = self.crane.apply(&mut self.stacks, instruction);
+
+        // Sources only shrink and targets only grow within an instruction, so
+        // measuring afterwards catches every peak and every emptied stack
+        self.costs.push(InstructionCost {
+            number,
+            description: instruction.to_string(),
+            crates: instruction.repeat,
+            lifts,
+            max_height: self.stacks.iter().map(|s| s.len()).max().unwrap_or(0),
+            emptied: (0..self.stacks.len())
+                .filter(|&i| self.stacks[i].is_empty() && !was_empty[i])
+                .map(|i| i + 1)
+                .collect(),
+        });
+        Ok(())
+    }
+
+    // Applies a parsed instruction, numbered from 1, failing if it didn't parse
+    fn apply_parsed(
+        &mut self,
+        number: usize,
+        parsed: &ParsedInstruction,
+    ) -> Result<(), CraneError> {
+        match parsed {
+            Ok(instruction) => self.apply_instruction(number, instruction),
+            Err(e) => Err(e.clone()),
         }
     }
 
-    pub fn apply_instruction_9000(&mut self, instruction: &Instruction) {
-        for _ in 0..instruction.repeat {
-            self.move_crates(instruction.from, instruction.to, 1);
+    // Validates then reverses a single instruction, numbered from 1
+    pub fn undo_instruction(
+        &mut self,
+        number: usize,
+        instruction: &Instruction,
+    ) -> Result<(), CraneError> {
+        self.validate(number, instruction, instruction.to)?;
+        self.crane.undo(&mut self.stacks, instruction);
+        Ok(())
+    }
+
+    // Runs instructions backwards from the last, recovering the stacks as
+    // they were before the first instruction
+    pub fn undo_instructions(
+        &mut self,
+        instructions: &[ParsedInstruction],
+    ) -> Result<(), CraneError> {
+        for (index, parsed) in instructions.iter().enumerate().rev() {
+            let instruction = parsed.as_ref().map_err(Clone::clone)?;
+            self.undo_instruction(index + 1, instruction)?;
         }
+        Ok(())
     }
 
-    pub fn apply_instruction_9001(&mut self, instruction: &Instruction) {
-        self.move_crates(instruction.from, instruction.to, instruction.repeat);
+    // Applies instructions in order. Invalid instructions either abort the
+    // run, or are skipped and returned as warnings.
+    pub fn apply_instructions(
+        &mut self,
+        instructions: &[ParsedInstruction],
+        on_error: OnError,
+    ) -> Result<Vec<CraneError>, CraneError> {
+        let mut warnings = vec![];
+        for (index, parsed) in instructions.iter().enumerate() {
+            if let Err(e) = self.apply_parsed(index + 1, parsed) {
+                match on_error {
+                    OnError::Abort => return Err(e),
+                    OnError::Skip => warnings.push(e),
+                }
+            }
+        }
+        Ok(warnings)
     }
 
-    pub fn apply_instructions(&mut self, instructions: &[Instruction]) {
-        instructions.iter().for_each(|i| {
-            if self.is_crane_version_9001 {
-                self.apply_instruction_9001(i);
-            } else {
-                self.apply_instruction_9000(i);
+    pub fn apply_instructions_traced(
+        &mut self,
+        instructions: &[ParsedInstruction],
+        on_error: OnError,
+    ) -> Result<Trace, CraneError> {
+        let mut trace = Trace::new(&self.stacks, &self.layout);
+        for (index, parsed) in instructions.iter().enumerate() {
+            let caption = match parsed {
+                Ok(instruction) => format!("Step {}: {}", index + 1, instruction),
+                Err(_) => format!("Step {}", index + 1),
+            };
+            match (self.apply_parsed(index + 1, parsed), on_error) {
+                (Ok(_), _) => trace.record(caption, &self.stacks),
+                (Err(e), OnError::Skip) => {
+                    trace.record(format!("{} (skipped: {})", caption, e), &self.stacks)
+                }
+                (Err(e), OnError::Abort) => return Err(e),
             }
-        });
+        }
+        Ok(trace)
+    }
+
+    // Totals the costs of every instruction applied so far
+    pub fn cost_report(&self, initial_height: usize) -> CostReport {
+        let mut emptied: Vec<usize> = self
+            .costs
+            .iter()
+            .flat_map(|c| c.emptied.iter().copied())
+            .collect();
+        emptied.sort_unstable();
+        emptied.dedup();
+
+        CostReport {
+            crane: self.crane.name(),
+            crates: self.costs.iter().map(|c| c.crates).sum(),
+            lifts: self.costs.iter().map(|c| c.lifts).sum(),
+            max_height: self
+                .costs
+                .iter()
+                .map(|c| c.max_height)
+                .fold(initial_height, usize::max),
+            emptied,
+            instructions: self.costs.clone(),
+        }
     }
 
     pub fn read_top_layer(&self) -> String {
-        String::from_iter(self.stacks.iter().filter_map(|s| s.last()))
+        String::from_iter(
+            self.stacks
+                .iter()
+                .filter_map(|s| s.last().map(String::as_str)),
+        )
+    }
+}
+
+// Lists stack numbers, or a dash when there are none
+fn format_stacks(stacks: &[usize]) -> String {
+    if stacks.is_empty() {
+        return String::from("-");
+    }
+    let numbers: Vec<String> = stacks.iter().map(|s| s.to_string()).collect();
+    numbers.join(",")
+}
+
+/**
+ * What it cost the crane to carry out one instruction
+ */
+#[derive(Clone)]
+struct InstructionCost {
+    number: usize,
+    description: String,
+    crates: usize,
+    lifts: usize,
+    max_height: usize,
+    emptied: Vec<usize>,
+}
+
+impl fmt::Display for InstructionCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>5}  {:<24}{:>7}{:>7}{:>8}  {}",
+            self.number,
+            self.description,
+            self.crates,
+            self.lifts,
+            self.max_height,
+            format_stacks(&self.emptied)
+        )
+    }
+}
+
+/**
+ * Costs of every applied instruction, and their totals over the whole run
+ */
+struct CostReport {
+    crane: String,
+    crates: usize,
+    lifts: usize,
+    max_height: usize,
+    emptied: Vec<usize>,
+    instructions: Vec<InstructionCost>,
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Costs for {}", self.crane)?;
+        writeln!(
+            f,
+            "{:>5}  {:<24}{:>7}{:>7}{:>8}  Emptied",
+            "Step", "Instruction", "Crates", "Lifts", "Height"
+        )?;
+        for cost in &self.instructions {
+            writeln!(f, "{}", cost)?;
+        }
+        writeln!(f, "Crates moved: {}", self.crates)?;
+        writeln!(f, "Lifts:        {}", self.lifts)?;
+        writeln!(f, "Max height:   {}", self.max_height)?;
+        write!(f, "Emptied:      {}", format_stacks(&self.emptied))
+    }
+}
+
+// Renders the ship exactly as it appears in the puzzle input
+impl fmt::Display for Ship {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            render_stacks(&self.stacks, &self.layout, self.padded)
+        )
+    }
+}
+
+/**
+ * Snapshot of a ship's stacks, taken before any instructions run and after
+ * each one
+ */
+struct Frame {
+    caption: String,
+    stacks: Vec<Stack>,
+    layout: Layout,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\n\n{}",
+            self.caption,
+            render_stacks(&self.stacks, &self.layout, false)
+        )
+    }
+}
+
+struct Trace {
+    frames: Vec<Frame>,
+    layout: Layout,
+}
+
+impl Trace {
+    pub fn new(initial: &[Stack], layout: &Layout) -> Trace {
+        let mut trace = Trace {
+            frames: vec![],
+            layout: layout.clone(),
+        };
+        trace.record(String::from("Initial state"), initial);
+        trace
+    }
+
+    pub fn record(&mut self, caption: String, stacks: &[Stack]) {
+        self.frames.push(Frame {
+            caption,
+            stacks: stacks.to_vec(),
+            layout: self.layout.clone(),
+        });
+    }
+
+    // Plays frames back in the terminal, clearing the screen between each
+    pub fn play(&self, delay: Duration) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        for frame in &self.frames {
+            writeln!(stdout, "\x1b[2J\x1b[H{}", frame)?;
+            stdout.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+
+    // Writes every frame to a file, separated by blank lines
+    pub fn dump(&self, path: &str) -> io::Result<()> {
+        let frames: Vec<String> = self.frames.iter().map(|f| f.to_string()).collect();
+        fs::write(path, frames.join("\n\n") + "\n")
     }
 }
 
 fn create_ship_and_instructions(
     input: &[Vec<String>],
-    is_crane_version_9001: bool,
-) -> (Ship, Vec<Instruction>) {
+    crane: Box<dyn Crane>,
+) -> (Ship, Vec<ParsedInstruction>) {
     let mut iter = input.iter();
     let raw_ship = iter.next().expect("Invalid input");
     let raw_instructions = iter.next().expect("Invalid input");
 
-    let ship = Ship::new(raw_ship, is_crane_version_9001);
+    let ship = Ship::new(raw_ship, crane);
     let instructions = raw_instructions
         .iter()
-        .map(|i| i.parse::<Instruction>().expect("Invalid input"))
+        .enumerate()
+        .map(|(index, i)| {
+            i.parse::<Instruction>()
+                .map_err(|reason| CraneError::InvalidInstruction {
+                    instruction: index + 1,
+                    reason,
+                })
+        })
         .collect();
 
     (ship, instructions)
 }
 
-fn simulate_ship(input: &[Vec<String>], is_crane_version_9001: bool) -> String {
-    let (mut ship, instructions) = create_ship_and_instructions(input, is_crane_version_9001);
-    ship.apply_instructions(&instructions);
-    ship.read_top_layer()
+fn simulate_ship(
+    input: &[Vec<String>],
+    crane: Box<dyn Crane>,
+    on_error: OnError,
+) -> Result<String, CraneError> {
+    let (mut ship, instructions) = create_ship_and_instructions(input, crane);
+    for warning in ship.apply_instructions(&instructions, on_error)? {
+        println!("Warning: skipped {}", warning);
+    }
+    Ok(ship.read_top_layer())
+}
+
+// Unwraps a result, panicking with a readable message for invalid instructions
+fn checked<T>(result: Result<T, CraneError>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
 }
 
 // PART 1
@@ -150,8 +779,16 @@ pub fn part1() {
 
     let data = input::day_input_grouped::<String>(5);
 
-    let result_test = simulate_ship(&data.test, false);
-    let result_real = simulate_ship(&data.real, false);
+    let result_test = checked(simulate_ship(
+        &data.test,
+        Box::new(CrateMover9000),
+        OnError::Abort,
+    ));
+    let result_real = checked(simulate_ship(
+        &data.real,
+        Box::new(CrateMover9000),
+        OnError::Abort,
+    ));
 
     print::answer_with_test(result_real, result_test);
 }
@@ -163,8 +800,194 @@ pub fn part2() {
 
     let data = input::day_input_grouped::<String>(5);
 
-    let result_test = simulate_ship(&data.test, true);
-    let result_real = simulate_ship(&data.real, true);
+    let result_test = checked(simulate_ship(
+        &data.test,
+        Box::new(CrateMover9001),
+        OnError::Abort,
+    ));
+    let result_real = checked(simulate_ship(
+        &data.real,
+        Box::new(CrateMover9001),
+        OnError::Abort,
+    ));
 
     print::answer_with_test(result_real, result_test);
 }
+
+// TRACE
+// Replay the crane's moves one instruction at a time
+
+enum TraceOutput<'a> {
+    Play(Duration),
+    Dump(&'a str),
+}
+
+fn trace(crane: &str, use_real: bool, output: TraceOutput) {
+    let data = input::day_input_grouped::<String>(5);
+    let input = if use_real { &data.real } else { &data.test };
+
+    let crane = match parse_crane(crane) {
+        Ok(crane) => crane,
+        Err(e) => return println!("{}", e),
+    };
+    let (mut ship, instructions) = create_ship_and_instructions(input, crane);
+    let trace = match ship.apply_instructions_traced(&instructions, OnError::Skip) {
+        Ok(trace) => trace,
+        Err(e) => return println!("{}", e),
+    };
+
+    let result = match output {
+        TraceOutput::Play(delay) => trace.play(delay),
+        TraceOutput::Dump(path) => trace.dump(path).map(|_| {
+            println!("Wrote {} frames to {}", trace.frames.len(), path);
+        }),
+    };
+    if let Err(e) = result {
+        println!("Unable to write trace: {}", e);
+    }
+}
+
+pub fn play(crane: &str, use_real: bool, delay_ms: u64) {
+    trace(
+        crane,
+        use_real,
+        TraceOutput::Play(Duration::from_millis(delay_ms)),
+    );
+}
+
+pub fn dump(crane: &str, use_real: bool, path: &str) {
+    trace(crane, use_real, TraceOutput::Dump(path));
+}
+
+// RENDER
+// Check that parsing then rendering a diagram gives back the original text
+
+pub fn render() {
+    println!("=== DAY 5, RENDER ===");
+    let data = input::day_input_grouped::<String>(5);
+
+    for (name, input) in [("Test", &data.test), ("Real", &data.real)] {
+        let diagram = input.first().expect("Invalid input");
+        let ship = Ship::new(diagram, Box::new(CrateMover9000));
+        let rendered = ship.to_string();
+
+        println!("{}:\n{}", name, rendered);
+        if rendered == diagram.join("\n") {
+            println!("Round trip: OK\n");
+        } else {
+            println!("Round trip: MISMATCH\n");
+        }
+    }
+}
+
+// CHECK
+// Run instructions from any file, aborting or skipping invalid ones
+
+pub fn check(crane: &str, path: &str, skip: bool) {
+    println!("=== DAY 5, CHECK {} ===", path);
+
+    let crane = match parse_crane(crane) {
+        Ok(crane) => crane,
+        Err(e) => return println!("{}\n", e),
+    };
+    println!("Using {}", crane.name());
+
+    let on_error = if skip { OnError::Skip } else { OnError::Abort };
+    let data = input::file_input_grouped::<String>(path);
+    match simulate_ship(&data, crane, on_error) {
+        Ok(top) => print::answer(top),
+        Err(e) => println!("Aborted: {}\n", e),
+    }
+}
+
+// REVERSE
+// Recover the initial stacks from a final state and the instructions that led
+// to it
+
+// Runs instructions backwards from a final diagram, then forwards again to
+// confirm the recovered stacks really do lead to the final state
+fn reverse_solve(input: &[Vec<String>], crane: Box<dyn Crane>) -> Result<(Ship, bool), CraneError> {
+    let (mut ship, instructions) = create_ship_and_instructions(input, crane);
+    let final_stacks = ship.stacks.clone();
+
+    ship.undo_instructions(&instructions)?;
+    let initial_stacks = ship.stacks.clone();
+
+    ship.apply_instructions(&instructions, OnError::Abort)?;
+    let consistent = ship.stacks == final_stacks;
+    ship.stacks = initial_stacks;
+
+    Ok((ship, consistent))
+}
+
+// Reads a final diagram followed by instructions from a file, and prints the
+// diagram the instructions must have started from
+pub fn reverse(crane: &str, path: &str) {
+    println!("=== DAY 5, REVERSE {} ===", path);
+
+    let crane = match parse_crane(crane) {
+        Ok(crane) => crane,
+        Err(e) => return println!("{}\n", e),
+    };
+    println!("Using {}", crane.name());
+
+    let data = input::file_input_grouped::<String>(path);
+    match reverse_solve(&data, crane) {
+        Ok((ship, true)) => println!("Initial state:\n{}\n", ship),
+        Ok((ship, false)) => println!("Inconsistent; best guess:\n{}\n", ship),
+        Err(e) => println!("Inconsistent: {}\n", e),
+    }
+}
+
+// Runs the puzzle inputs forwards then backwards with both crane models,
+// checking the original diagram comes back
+pub fn verify_reverse() {
+    println!("=== DAY 5, VERIFY REVERSE ===");
+    let data = input::day_input_grouped::<String>(5);
+
+    for (name, input) in [("Test", &data.test), ("Real", &data.real)] {
+        for crane in ["9000", "9001"] {
+            let (mut ship, instructions) =
+                create_ship_and_instructions(input, parse_crane(crane).unwrap());
+            let initial = ship.stacks.clone();
+
+            checked(ship.apply_instructions(&instructions, OnError::Abort));
+            let final_input: Vec<Vec<String>> = vec![
+                ship.to_string().lines().map(String::from).collect(),
+                input[1].clone(),
+            ];
+
+            let (recovered, consistent) =
+                checked(reverse_solve(&final_input, parse_crane(crane).unwrap()));
+            let status = if consistent && recovered.stacks == initial {
+                "OK"
+            } else {
+                "MISMATCH"
+            };
+            println!("{} with {}: {}", name, recovered.crane.name(), status);
+        }
+    }
+    println!();
+}
+
+// COSTS
+// Report what each instruction cost the crane, alongside the top layer
+
+pub fn costs(crane: &str, use_real: bool) {
+    println!("=== DAY 5, COSTS ===");
+    let data = input::day_input_grouped::<String>(5);
+    let input = if use_real { &data.real } else { &data.test };
+
+    let crane = match parse_crane(crane) {
+        Ok(crane) => crane,
+        Err(e) => return println!("{}\n", e),
+    };
+    let (mut ship, instructions) = create_ship_and_instructions(input, crane);
+    let initial_height = ship.stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+    for warning in checked(ship.apply_instructions(&instructions, OnError::Skip)) {
+        println!("Warning: skipped {}", warning);
+    }
+
+    println!("{}", ship.cost_report(initial_height));
+    print::answer(ship.read_top_layer());
+}
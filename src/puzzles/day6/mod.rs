@@ -1,57 +1,404 @@
+use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufReader, Read};
+use std::iter;
+use std::str::FromStr;
 
 use crate::input;
 use crate::print;
 
-struct Header {
+/**
+ * A symbol that can be counted in a sliding window. Bytes are counted in a
+ * flat table, so scanning raw bytes never hashes or allocates; other symbols
+ * are counted in a hash map that only holds what's in the window.
+ */
+trait Symbol: Clone {
+    type Counts;
+
+    fn new_counts(window: usize) -> Self::Counts;
+
+    // Adds or removes one of a symbol, returning its new count
+    fn add(counts: &mut Self::Counts, symbol: &Self) -> usize;
+    fn remove(counts: &mut Self::Counts, symbol: &Self) -> usize;
+}
+
+impl Symbol for u8 {
+    type Counts = [usize; 256];
+
+    fn new_counts(_window: usize) -> Self::Counts {
+        [0; 256]
+    }
+
+    fn add(counts: &mut Self::Counts, symbol: &u8) -> usize {
+        counts[*symbol as usize] += 1;
+        counts[*symbol as usize]
+    }
+
+    fn remove(counts: &mut Self::Counts, symbol: &u8) -> usize {
+        counts[*symbol as usize] -= 1;
+        counts[*symbol as usize]
+    }
+}
+
+fn hashed_add<T: Eq + Hash + Clone>(counts: &mut HashMap<T, usize>, symbol: &T) -> usize {
+    let count = counts.entry(symbol.clone()).or_insert(0);
+    *count += 1;
+    *count
+}
+
+fn hashed_remove<T: Eq + Hash>(counts: &mut HashMap<T, usize>, symbol: &T) -> usize {
+    let count = counts.get_mut(symbol).unwrap();
+    *count -= 1;
+    let count = *count;
+    if count == 0 {
+        counts.remove(symbol);
+    }
+    count
+}
+
+impl Symbol for char {
+    type Counts = HashMap<char, usize>;
+
+    fn new_counts(window: usize) -> Self::Counts {
+        HashMap::with_capacity(window + 1)
+    }
+
+    fn add(counts: &mut Self::Counts, symbol: &char) -> usize {
+        hashed_add(counts, symbol)
+    }
+
+    fn remove(counts: &mut Self::Counts, symbol: &char) -> usize {
+        hashed_remove(counts, symbol)
+    }
+}
+
+impl Symbol for &str {
+    type Counts = HashMap<Self, usize>;
+
+    fn new_counts(window: usize) -> Self::Counts {
+        HashMap::with_capacity(window + 1)
+    }
+
+    fn add(counts: &mut Self::Counts, symbol: &Self) -> usize {
+        hashed_add(counts, symbol)
+    }
+
+    fn remove(counts: &mut Self::Counts, symbol: &Self) -> usize {
+        hashed_remove(counts, symbol)
+    }
+}
+
+impl Symbol for String {
+    type Counts = HashMap<String, usize>;
+
+    fn new_counts(window: usize) -> Self::Counts {
+        HashMap::with_capacity(window + 1)
+    }
+
+    fn add(counts: &mut Self::Counts, symbol: &String) -> usize {
+        hashed_add(counts, symbol)
+    }
+
+    fn remove(counts: &mut Self::Counts, symbol: &String) -> usize {
+        hashed_remove(counts, symbol)
+    }
+}
+
+/**
+ * Sliding window over the most recent symbols of a datastream. Tracks how
+ * many times each symbol appears in the window, and how many symbols appear
+ * more than once, so checking uniqueness doesn't need to rescan the window.
+ */
+struct Header<T: Symbol> {
     size: usize,
-    bytes: VecDeque<u8>,
+    symbols: VecDeque<T>,
+    counts: T::Counts,
+    duplicates: usize,
 }
 
-impl Header {
-    pub fn new(size: usize) -> Header {
+impl<T: Symbol> Header<T> {
+    pub fn new(size: usize) -> Header<T> {
         Header {
             size,
-            bytes: VecDeque::new(),
+            symbols: VecDeque::with_capacity(size + 1),
+            counts: T::new_counts(size),
+            duplicates: 0,
         }
     }
 
-    pub fn append(&mut self, byte: u8) {
-        self.bytes.push_back(byte);
-        while self.bytes.len() > self.size {
-            self.bytes.pop_front();
+    pub fn append(&mut self, symbol: T) {
+        if T::add(&mut self.counts, &symbol) == 2 {
+            self.duplicates += 1;
+        }
+        self.symbols.push_back(symbol);
+
+        if self.symbols.len() > self.size {
+            let old = self.symbols.pop_front().unwrap();
+            if T::remove(&mut self.counts, &old) == 1 {
+                self.duplicates -= 1;
+            }
         }
     }
 
     pub fn is_full(&self) -> bool {
-        self.bytes.len() == self.size
+        self.symbols.len() == self.size
     }
 
     pub fn unique(&self) -> bool {
-        for i in 1..self.bytes.len() {
-            let byte = *self.bytes.get(i).unwrap();
-            for j in 0..i {
-                let cmp = *self.bytes.get(j).unwrap();
-                if cmp == byte {
-                    return false;
+        self.duplicates == 0
+    }
+}
+
+/**
+ * Built-in ways of splitting a datastream into symbols. Marker positions are
+ * counted in symbols, so they only match byte offsets for `Bytes`. Any other
+ * tokenizer can be used by passing its symbols to `scan_symbols`.
+ */
+#[derive(Debug, Clone, Copy)]
+enum Alphabet {
+    // Every byte is a symbol
+    Bytes,
+    // Every character is a symbol
+    Chars,
+    // Only alphabetic characters are symbols; anything else is skipped
+    Letters,
+    // Whitespace separated words are symbols
+    Words,
+}
+
+impl FromStr for Alphabet {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytes" => Ok(Alphabet::Bytes),
+            "chars" => Ok(Alphabet::Chars),
+            "letters" => Ok(Alphabet::Letters),
+            "words" => Ok(Alphabet::Words),
+            _ => Err(format!("Unknown alphabet {:?}", s)),
+        }
+    }
+    type Err = String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Marker {
+    size: usize,
+    offset: usize,
+}
+
+/**
+ * Scans a datastream one symbol at a time for the first marker of each window
+ * size, so input never has to be held in memory
+ */
+struct MarkerScanner<T: Symbol> {
+    headers: Vec<Header<T>>,
+    found: Vec<bool>,
+    position: usize,
+}
+
+impl<T: Symbol> MarkerScanner<T> {
+    pub fn new(sizes: &[usize]) -> MarkerScanner<T> {
+        MarkerScanner {
+            headers: sizes.iter().map(|&size| Header::new(size)).collect(),
+            found: vec![false; sizes.len()],
+            position: 0,
+        }
+    }
+
+    // Feeds the next symbol, returning any markers that end with it
+    pub fn feed(&mut self, symbol: T) -> Vec<Marker> {
+        self.position += 1;
+
+        let mut markers = vec![];
+        for (header, found) in self.headers.iter_mut().zip(self.found.iter_mut()) {
+            if *found {
+                continue;
+            }
+            header.append(symbol.clone());
+            if header.is_full() && header.unique() {
+                *found = true;
+                markers.push(Marker {
+                    size: header.size,
+                    offset: self.position,
+                });
+            }
+        }
+        markers
+    }
+
+    // Checks if every window size has found its marker
+    pub fn is_done(&self) -> bool {
+        self.found.iter().all(|f| *f)
+    }
+}
+
+// Lazily finds the first marker of each size in a sequence of symbols,
+// stopping once all have been found
+fn scan_symbols<T, I>(symbols: I, sizes: &[usize]) -> impl Iterator<Item = Marker>
+where
+    T: Symbol,
+    I: IntoIterator<Item = T>,
+{
+    let mut scanner = MarkerScanner::new(sizes);
+    symbols
+        .into_iter()
+        .map_while(move |symbol| (!scanner.is_done()).then(|| scanner.feed(symbol)))
+        .flatten()
+}
+
+// Decodes UTF-8 characters from a reader one at a time, so streamed markers
+// are counted in characters without reading the whole input
+fn read_chars<R: Read>(reader: R) -> impl Iterator<Item = io::Result<char>> {
+    let mut bytes = BufReader::new(reader).bytes();
+    iter::from_fn(move || {
+        let first = match bytes.next()? {
+            Ok(byte) => byte,
+            Err(e) => return Some(Err(e)),
+        };
+        let len = match first {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        let mut buffer = [first, 0, 0, 0];
+        for slot in buffer.iter_mut().take(len).skip(1) {
+            match bytes.next() {
+                Some(Ok(byte)) => *slot = byte,
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            }
+        }
+        let decoded = std::str::from_utf8(&buffer[..len])
+            .ok()
+            .and_then(|s| s.chars().next());
+        Some(
+            decoded.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "stream is not valid UTF-8")
+            }),
+        )
+    })
+}
+
+// Groups characters from a stream into whitespace separated words
+fn read_words<R: Read>(reader: R) -> impl Iterator<Item = io::Result<String>> {
+    let mut chars = read_chars(reader);
+    iter::from_fn(move || {
+        let mut word = String::new();
+        for c in chars.by_ref() {
+            match c {
+                Ok(c) if c.is_whitespace() => {
+                    if !word.is_empty() {
+                        return Some(Ok(word));
+                    }
                 }
+                Ok(c) => word.push(c),
+                Err(e) => return Some(Err(e)),
             }
         }
-        true
+        (!word.is_empty()).then_some(Ok(word))
+    })
+}
+
+// Finds the first marker of each size in a reader, counting positions in
+// symbols of the given alphabet and calling `on_marker` as each is found.
+// Stops at the end of input, once all markers are found, or when `on_marker`
+// returns false.
+fn scan_reader<R, F>(reader: R, sizes: &[usize], alphabet: Alphabet, on_marker: F) -> io::Result<()>
+where
+    R: Read,
+    F: FnMut(Marker) -> bool,
+{
+    match alphabet {
+        Alphabet::Bytes => scan_stream(BufReader::new(reader).bytes(), sizes, on_marker),
+        Alphabet::Chars => scan_stream(read_chars(reader), sizes, on_marker),
+        Alphabet::Letters => scan_stream(
+            read_chars(reader).filter(|c| c.as_ref().map_or(true, |c| c.is_alphabetic())),
+            sizes,
+            on_marker,
+        ),
+        Alphabet::Words => scan_stream(read_words(reader), sizes, on_marker),
+    }
+}
+
+fn scan_stream<T, I, F>(symbols: I, sizes: &[usize], mut on_marker: F) -> io::Result<()>
+where
+    T: Symbol,
+    I: Iterator<Item = io::Result<T>>,
+    F: FnMut(Marker) -> bool,
+{
+    let mut scanner = MarkerScanner::new(sizes);
+    for symbol in symbols {
+        for marker in scanner.feed(symbol?) {
+            if !on_marker(marker) {
+                return Ok(());
+            }
+        }
+        if scanner.is_done() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn find_symbol_marker<T, I>(symbols: I, header_size: usize) -> Option<usize>
+where
+    T: Symbol,
+    I: IntoIterator<Item = T>,
+{
+    scan_symbols(symbols, &[header_size])
+        .next()
+        .map(|marker| marker.offset)
+}
+
+// Finds the first marker in a datastream, counting position in symbols of
+// the given alphabet
+fn find_marker_in(datastream: &str, header_size: usize, alphabet: Alphabet) -> Option<usize> {
+    match alphabet {
+        Alphabet::Bytes => find_symbol_marker(datastream.bytes(), header_size),
+        Alphabet::Chars => find_symbol_marker(datastream.chars(), header_size),
+        Alphabet::Letters => find_symbol_marker(
+            datastream.chars().filter(|c| c.is_alphabetic()),
+            header_size,
+        ),
+        Alphabet::Words => find_symbol_marker(datastream.split_whitespace(), header_size),
     }
 }
 
 fn find_marker(datastream: &str, header_size: usize) -> Option<usize> {
+    find_marker_in(datastream, header_size, Alphabet::Chars)
+}
+
+// Finds every marker of a size, starting a fresh window after each one so
+// markers never overlap
+fn find_all_markers<T: Symbol>(symbols: &[T], header_size: usize) -> Vec<Marker> {
     let mut header = Header::new(header_size);
+    let mut markers = vec![];
 
-    for (index, byte) in datastream.as_bytes().iter().enumerate() {
-        header.append(*byte);
+    for (index, symbol) in symbols.iter().enumerate() {
+        header.append(symbol.clone());
         if header.is_full() && header.unique() {
-            return Some(index + 1);
+            markers.push(Marker {
+                size: header_size,
+                offset: index + 1,
+            });
+            header = Header::new(header_size);
         }
     }
 
-    None
+    markers
+}
+
+// Finds a marker by comparing every pair of chars in each window; used to
+// check `find_marker` gives the same results
+fn find_marker_pairwise(datastream: &str, header_size: usize) -> Option<usize> {
+    let chars: Vec<char> = datastream.chars().collect();
+    (header_size..=chars.len()).find(|&end| {
+        let window = &chars[end - header_size..end];
+        (1..window.len()).all(|i| !window[..i].contains(&window[i]))
+    })
 }
 
 // PART 1
@@ -77,3 +424,172 @@ pub fn part2() {
 
     print::answer_with_test(pos_real, pos_test);
 }
+
+// SEGMENT
+// List every marker, and split the datastream into the messages between them
+
+/**
+ * A message, running from the end of its start-of-message marker to the start
+ * of the next one (or the end of the datastream)
+ */
+struct Message {
+    marker: Marker,
+    start: usize,
+    length: usize,
+    packets: usize,
+}
+
+struct DatastreamReport {
+    packets: Vec<Marker>,
+    messages: Vec<Message>,
+}
+
+impl DatastreamReport {
+    pub fn new<T: Symbol>(
+        symbols: &[T],
+        packet_size: usize,
+        message_size: usize,
+    ) -> DatastreamReport {
+        let packets = find_all_markers(symbols, packet_size);
+        let markers = find_all_markers(symbols, message_size);
+
+        let messages = markers
+            .iter()
+            .enumerate()
+            .map(|(index, marker)| {
+                let start = marker.offset;
+                let end = markers
+                    .get(index + 1)
+                    .map_or(symbols.len(), |next| next.offset - next.size);
+                Message {
+                    marker: *marker,
+                    start,
+                    length: end.saturating_sub(start),
+                    packets: packets
+                        .iter()
+                        .filter(|p| p.offset > start && p.offset <= end)
+                        .count(),
+                }
+            })
+            .collect();
+
+        DatastreamReport { packets, messages }
+    }
+
+    // Builds a report for a datastream split into symbols of an alphabet
+    pub fn for_alphabet(
+        datastream: &str,
+        packet_size: usize,
+        message_size: usize,
+        alphabet: Alphabet,
+    ) -> DatastreamReport {
+        let datastream = datastream.trim_end();
+        match alphabet {
+            Alphabet::Bytes => {
+                DatastreamReport::new(datastream.as_bytes(), packet_size, message_size)
+            }
+            Alphabet::Chars => {
+                let chars: Vec<char> = datastream.chars().collect();
+                DatastreamReport::new(&chars, packet_size, message_size)
+            }
+            Alphabet::Letters => {
+                let letters: Vec<char> = datastream.chars().filter(|c| c.is_alphabetic()).collect();
+                DatastreamReport::new(&letters, packet_size, message_size)
+            }
+            Alphabet::Words => {
+                let words: Vec<&str> = datastream.split_whitespace().collect();
+                DatastreamReport::new(&words, packet_size, message_size)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DatastreamReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offsets: Vec<String> = self.packets.iter().map(|p| p.offset.to_string()).collect();
+        writeln!(
+            f,
+            "{} start-of-packet markers at: {}",
+            self.packets.len(),
+            offsets.join(", ")
+        )?;
+        write!(f, "{} messages", self.messages.len())?;
+        for (index, message) in self.messages.iter().enumerate() {
+            write!(
+                f,
+                "\n  #{}: marker at {}, data {}..{} ({} symbols, {} packet markers)",
+                index + 1,
+                message.marker.offset,
+                message.start,
+                message.start + message.length,
+                message.length,
+                message.packets
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn segment(packet_size: usize, message_size: usize, alphabet: &str) {
+    println!("=== DAY 6, SEGMENT ({}) ===", alphabet);
+
+    let alphabet: Alphabet = match alphabet.parse() {
+        Ok(alphabet) => alphabet,
+        Err(e) => return println!("{}\n", e),
+    };
+    let data = input::day_input_raw(6);
+    let report_test =
+        DatastreamReport::for_alphabet(&data.test, packet_size, message_size, alphabet);
+    let report_real =
+        DatastreamReport::for_alphabet(&data.real, packet_size, message_size, alphabet);
+
+    print::answer_with_test_newline(report_real, report_test);
+}
+
+// VERIFY
+// Check the sliding window detector against the pairwise one
+
+pub fn verify() {
+    println!("=== DAY 6, VERIFY ===");
+
+    let data = input::day_input_raw(6);
+    for (name, stream) in [("Test", &data.test), ("Real", &data.real)] {
+        for size in [4, 14] {
+            let expected = find_marker_pairwise(stream, size);
+            let actual = find_marker(stream, size);
+            let status = if actual == expected { "OK" } else { "MISMATCH" };
+            println!(
+                "{} window {:>2}: {:?} vs {:?} {}",
+                name, size, actual, expected, status
+            );
+        }
+    }
+    println!();
+}
+
+// STREAM
+// Report markers from a file or stdin as soon as they're found
+
+pub fn stream(sizes: &[usize], path: Option<&str>, alphabet: &str) {
+    println!("=== DAY 6, STREAM ({}) ===", alphabet);
+
+    let alphabet: Alphabet = match alphabet.parse() {
+        Ok(alphabet) => alphabet,
+        Err(e) => return println!("{}\n", e),
+    };
+    let report = |marker: Marker| {
+        println!("Window {:>2}: marker at {}", marker.size, marker.offset);
+        true
+    };
+    let result = match path {
+        Some(path) if path != "-" => {
+            File::open(path).and_then(|f| scan_reader(f, sizes, alphabet, report))
+        }
+        _ => scan_reader(io::stdin().lock(), sizes, alphabet, report),
+    };
+
+    if let Err(e) = result {
+        println!("Error: {}", e);
+    }
+    println!();
+}
@@ -1,60 +1,188 @@
+use std::array;
 use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
 use std::str::FromStr;
-use std::string::ParseError;
+use std::thread;
+use std::time::Duration;
 
 use crate::input;
 use crate::print;
 
-type Coord = (isize, isize);
+// Position along each axis: x then y, then z for 3D ropes. Y grows downwards,
+// like the puzzle's diagrams.
+type Point<const N: usize> = [isize; N];
+type Coord = Point<2>;
 
-struct Movement {
-    delta: Coord,
-    repeat: usize,
+// Unit direction for each letter, as an axis and a sign
+const DIRECTIONS: [(char, usize, isize); 6] = [
+    ('L', 0, -1),
+    ('R', 0, 1),
+    ('U', 1, -1),
+    ('D', 1, 1),
+    ('B', 2, -1),
+    ('F', 2, 1),
+];
+
+enum Movement<const N: usize> {
+    // Steps the head by `delta` a number of times
+    Step { delta: Point<N>, repeat: usize },
+    // Walks the head to an absolute position, one step at a time
+    Goto(Point<N>),
+}
+
+// Combines direction letters into a single step, so "UL" moves up and left
+// at once. Each axis can only be used once.
+fn parse_direction<const N: usize>(dir: &str) -> Result<Point<N>, String> {
+    let mut delta = [0; N];
+    for letter in dir.chars() {
+        match DIRECTIONS.iter().find(|(l, _, _)| *l == letter) {
+            Some(&(_, axis, _)) if axis >= N => {
+                return Err(format!("Direction {:?} needs a {}D rope", letter, axis + 1))
+            }
+            Some(&(_, axis, sign)) if delta[axis] == 0 => delta[axis] = sign,
+            Some(_) => return Err(format!("Invalid direction {:?}", dir)),
+            None => return Err(format!("Unknown direction {:?}", letter)),
+        }
+    }
+    if delta == [0; N] {
+        return Err(String::from("Missing direction"));
+    }
+    Ok(delta)
+}
+
+// Parses comma separated coordinates, e.g. "3,-4"
+fn parse_point<const N: usize>(s: &str) -> Result<Point<N>, String> {
+    let values: Vec<isize> = s
+        .split(',')
+        .map(|v| v.trim().parse::<isize>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid position {:?}: {}", s, e))?;
+    values
+        .try_into()
+        .map_err(|_| format!("Position {:?} needs {} coordinates", s, N))
 }
 
-impl FromStr for Movement {
+impl<const N: usize> FromStr for Movement<N> {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut parts = s.split_ascii_whitespace();
-        let dir = parts.next().unwrap();
-        let repeat: usize = parts.next().unwrap().parse().unwrap();
-        let delta: Coord = match dir {
-            // Up
-            "U" => (0, -1),
-            // Down
-            "D" => (0, 1),
-            // Left
-            "L" => (-1, 0),
-            // Right (catchall)
-            _ => (1, 0),
-        };
-        Ok(Movement { delta, repeat })
+        let dir = parts.next().ok_or("Empty movement")?;
+        let value = parts
+            .next()
+            .ok_or_else(|| format!("Missing value in {:?}", s))?;
+
+        if dir == "goto" {
+            return Ok(Movement::Goto(parse_point(value)?));
+        }
+        let repeat: usize = value
+            .parse()
+            .map_err(|e| format!("Invalid repeat in {:?}: {}", s, e))?;
+        Ok(Movement::Step {
+            delta: parse_direction(dir)?,
+            repeat,
+        })
     }
-    type Err = ParseError;
+    type Err = String;
 }
 
-struct Rope {
-    head: Coord,
-    knots: Vec<Coord>,
-    track_tail: HashSet<Coord>,
+// Writes the movement back out as it appears in the input
+impl<const N: usize> fmt::Display for Movement<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Movement::Step { delta, repeat } => {
+                // Vertical before horizontal, as in "UL"
+                let dir: String = [1, 0, 2]
+                    .iter()
+                    .filter_map(|&axis| {
+                        DIRECTIONS
+                            .iter()
+                            .find(|(_, a, sign)| *a == axis && delta.get(axis) == Some(sign))
+                    })
+                    .map(|(letter, _, _)| letter)
+                    .collect();
+                write!(f, "{} {}", dir, repeat)
+            }
+            Movement::Goto(target) => {
+                let coords: Vec<String> = target.iter().map(|c| c.to_string()).collect();
+                write!(f, "goto {}", coords.join(","))
+            }
+        }
+    }
+}
+
+/**
+ * Smallest rectangle containing a set of coordinates, inclusive at both ends
+ */
+#[derive(Clone, Copy)]
+struct Bounds {
+    min: Coord,
+    max: Coord,
 }
 
-impl Rope {
-    pub fn new(length: usize) -> Rope {
+impl Bounds {
+    pub fn new(coord: Coord) -> Bounds {
+        Bounds {
+            min: coord,
+            max: coord,
+        }
+    }
+
+    pub fn include(&mut self, [x, y]: Coord) {
+        self.min = [self.min[0].min(x), self.min[1].min(y)];
+        self.max = [self.max[0].max(x), self.max[1].max(y)];
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max[0] - self.min[0]) as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max[1] - self.min[1]) as usize + 1
+    }
+
+    // Every coordinate in the rectangle, row by row from the top
+    pub fn rows(&self) -> impl Iterator<Item = Vec<Coord>> + '_ {
+        (self.min[1]..=self.max[1]).map(|y| (self.min[0]..=self.max[0]).map(|x| [x, y]).collect())
+    }
+}
+
+/**
+ * A rope made of a head and a number of trailing knots, moving through N
+ * dimensions. Every knot keeps the set of cells it has visited and how many
+ * steps it has taken.
+ */
+struct Rope<const N: usize> {
+    head: Point<N>,
+    knots: Vec<Point<N>>,
+    visited: Vec<HashSet<Point<N>>>,
+    steps: Vec<usize>,
+}
+
+type Rope3D = Rope<3>;
+
+impl<const N: usize> Rope<N> {
+    pub fn new(length: usize) -> Rope<N> {
         Rope {
-            head: (0, 0),
-            knots: vec![(0, 0); length],
-            track_tail: HashSet::new(),
+            head: [0; N],
+            knots: vec![[0; N]; length],
+            visited: vec![HashSet::from([[0; N]]); length],
+            steps: vec![0; length],
         }
     }
 
-    fn move_head(&mut self, (dx, dy): &Coord) {
-        self.head.0 += dx;
-        self.head.1 += dy;
+    // Cells visited by the last knot
+    pub fn tail_visits(&self) -> &HashSet<Point<N>> {
+        self.visited.last().expect("Rope has no knots")
     }
 
-    fn move_knot(&mut self, index: usize) {
-        let count = self.knots.len();
+    fn move_head(&mut self, delta: &Point<N>) {
+        for (axis, d) in delta.iter().enumerate() {
+            self.head[axis] += d;
+        }
+    }
 
+    fn move_knot(&mut self, index: usize) {
         // Determine previous knot
         let prev_knot = if index == 0 {
             self.head
@@ -64,17 +192,15 @@ impl Rope {
 
         if let Some(knot) = self.knots.get_mut(index) {
             // Move knot towards prev knot
-            let dx = prev_knot.0 - knot.0;
-            let dy = prev_knot.1 - knot.1;
+            let diff: Point<N> = array::from_fn(|axis| prev_knot[axis] - knot[axis]);
 
-            if dx.abs() > 1 || dy.abs() > 1 {
-                knot.0 += dx.signum();
-                knot.1 += dy.signum();
-            }
+            if diff.iter().any(|d| d.abs() > 1) {
+                for (axis, d) in diff.iter().enumerate() {
+                    knot[axis] += d.signum();
+                }
 
-            // If index matches last knot, track it
-            if index == count - 1 {
-                self.track_tail.insert(*knot);
+                self.steps[index] += 1;
+                self.visited[index].insert(*knot);
             }
         }
     }
@@ -86,24 +212,108 @@ impl Rope {
         }
     }
 
-    fn apply_movement(&mut self, movement: &Movement) {
-        for _ in 0..movement.repeat {
-            self.move_head(&movement.delta);
-            self.move_knots();
+    fn apply_movement(&mut self, movement: &Movement<N>) {
+        match movement {
+            Movement::Step { delta, repeat } => {
+                for _ in 0..*repeat {
+                    self.move_head(delta);
+                    self.move_knots();
+                }
+            }
+            Movement::Goto(target) => {
+                while self.head != *target {
+                    let delta = array::from_fn(|axis| (target[axis] - self.head[axis]).signum());
+                    self.move_head(&delta);
+                    self.move_knots();
+                }
+            }
         }
     }
 
-    pub fn apply_movements(&mut self, movements: &[Movement]) {
+    pub fn apply_movements(&mut self, movements: &[Movement<N>]) {
         for m in movements {
             self.apply_movement(m)
         }
     }
 }
 
-fn simulate_rope(length: usize, movements: &[Movement]) -> usize {
+impl Rope<2> {
+    // Label for a knot, like the puzzle: T for a lone tail, otherwise numbers
+    fn knot_label(&self, index: usize) -> char {
+        if self.knots.len() == 1 {
+            'T'
+        } else {
+            char::from_digit(index as u32 + 1, 36).unwrap_or('*')
+        }
+    }
+
+    // Draws the rope within the given bounds. The head covers knots, earlier
+    // knots cover later ones, and the start and visited cells show beneath.
+    pub fn render(&self, bounds: &Bounds) -> String {
+        let rows: Vec<String> = bounds
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&coord| {
+                        if coord == self.head {
+                            'H'
+                        } else if let Some(i) = self.knots.iter().position(|&k| k == coord) {
+                            self.knot_label(i)
+                        } else if coord == [0, 0] {
+                            's'
+                        } else if self.tail_visits().contains(&coord) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        rows.join("\n")
+    }
+
+    // Plain PBM image of the cells the tail has visited, one pixel per cell
+    pub fn visited_image(&self) -> String {
+        let bounds = self
+            .tail_visits()
+            .iter()
+            .fold(Bounds::new([0, 0]), |mut b, &c| {
+                b.include(c);
+                b
+            });
+        let mut image = format!("P1\n{} {}\n", bounds.width(), bounds.height());
+        for row in bounds.rows() {
+            let pixels: Vec<&str> = row
+                .iter()
+                .map(|c| {
+                    if self.tail_visits().contains(c) {
+                        "1"
+                    } else {
+                        "0"
+                    }
+                })
+                .collect();
+            image += &pixels.join(" ");
+            image += "\n";
+        }
+        image
+    }
+}
+
+const KNOTS: usize = 9;
+
+fn simulate_rope<const N: usize>(length: usize, movements: &[Movement<N>]) -> Rope<N> {
     let mut rope = Rope::new(length);
     rope.apply_movements(movements);
-    rope.track_tail.len()
+    rope
+}
+
+// Number of cells visited by each knot. Knots never affect the ones ahead of
+// them, so knot K of a long rope moves exactly like the tail of a K knot rope.
+fn visit_counts(movements: &[Movement<2>]) -> Vec<usize> {
+    let rope = simulate_rope(KNOTS, movements);
+    rope.visited.iter().map(|v| v.len()).collect()
 }
 
 // PART 1
@@ -112,9 +322,9 @@ fn simulate_rope(length: usize, movements: &[Movement]) -> usize {
 pub fn part1() {
     print::intro(9, 1);
 
-    let data = input::day_input::<Movement>(9);
-    let area_test = simulate_rope(1, &data.test);
-    let area_real = simulate_rope(1, &data.real);
+    let data = input::day_input::<Movement<2>>(9);
+    let area_test = visit_counts(&data.test)[0];
+    let area_real = visit_counts(&data.real)[0];
 
     print::answer_with_test(area_real, area_test);
 }
@@ -125,9 +335,139 @@ pub fn part1() {
 pub fn part2() {
     print::intro(9, 2);
 
-    let data = input::day_input::<Movement>(9);
-    let area_test = simulate_rope(9, &data.test);
-    let area_real = simulate_rope(9, &data.real);
+    let data = input::day_input::<Movement<2>>(9);
+    let area_test = visit_counts(&data.test)[KNOTS - 1];
+    let area_real = visit_counts(&data.real)[KNOTS - 1];
 
     print::answer_with_test(area_real, area_test);
 }
+
+// BOTH PARTS
+// Both answers come from the same simulation, so only run it once
+
+pub fn both_parts() {
+    let data = input::day_input::<Movement<2>>(9);
+    let counts_test = visit_counts(&data.test);
+    let counts_real = visit_counts(&data.real);
+
+    print::intro(9, 1);
+    print::answer_with_test(counts_real[0], counts_test[0]);
+
+    print::intro(9, 2);
+    print::answer_with_test(counts_real[KNOTS - 1], counts_test[KNOTS - 1]);
+}
+
+// KNOTS
+// Cells visited and steps taken by every knot of a rope
+
+pub fn knots(length: usize, use_real: bool) {
+    println!("=== DAY 9, KNOTS ===");
+    let data = input::day_input::<Movement<2>>(9);
+    let movements = if use_real { &data.real } else { &data.test };
+
+    print_knots(&simulate_rope(length, movements));
+}
+
+fn print_knots<const N: usize>(rope: &Rope<N>) {
+    println!("{:>4}{:>9}{:>8}", "Knot", "Visited", "Steps");
+    for (i, (visited, steps)) in rope.visited.iter().zip(&rope.steps).enumerate() {
+        println!("{:>4}{:>9}{:>8}", i + 1, visited.len(), steps);
+    }
+    println!();
+}
+
+// Runs movements from any file, which may use diagonal directions like "UL"
+// and absolute "goto x,y" commands. 3D ropes also move forwards and
+// backwards with F and B, and go to "x,y,z".
+pub fn file(length: usize, path: &str, three_d: bool) {
+    println!("=== DAY 9, FILE {} ===", path);
+
+    if three_d {
+        let movements = input::file_input::<Movement<3>>(path);
+        let rope: Rope3D = simulate_rope(length, &movements);
+        print_knots(&rope);
+    } else {
+        let movements = input::file_input::<Movement<2>>(path);
+        print_knots(&simulate_rope(length, &movements));
+    }
+}
+
+// VISUALISE
+// Draw the rope after each movement, like the puzzle's examples
+
+// Area the head covers over every movement. Knots only ever step towards the
+// knot ahead of them, so they never leave it.
+fn movement_bounds(movements: &[Movement<2>]) -> Bounds {
+    let mut bounds = Bounds::new([0, 0]);
+    let mut head = [0, 0];
+    for m in movements {
+        head = match m {
+            Movement::Step { delta, repeat } => [
+                head[0] + delta[0] * *repeat as isize,
+                head[1] + delta[1] * *repeat as isize,
+            ],
+            Movement::Goto(target) => *target,
+        };
+        bounds.include(head);
+    }
+    bounds
+}
+
+fn play_frames(length: usize, movements: &[Movement<2>], delay: Duration) -> io::Result<()> {
+    let bounds = movement_bounds(movements);
+    let mut rope = Rope::new(length);
+    let mut stdout = io::stdout().lock();
+
+    writeln!(
+        stdout,
+        "\x1b[2J\x1b[H== Initial State ==\n\n{}",
+        rope.render(&bounds)
+    )?;
+    stdout.flush()?;
+    for m in movements {
+        thread::sleep(delay);
+        rope.apply_movement(m);
+        writeln!(
+            stdout,
+            "\x1b[2J\x1b[H== {} ==\n\n{}",
+            m,
+            rope.render(&bounds)
+        )?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+pub fn play(length: usize, use_real: bool, delay_ms: u64) {
+    // The tail's visited cells are drawn, so there must be a tail
+    if length == 0 {
+        return println!("A rope needs at least one knot to draw");
+    }
+    let data = input::day_input::<Movement<2>>(9);
+    let movements = if use_real { &data.real } else { &data.test };
+
+    if let Err(e) = play_frames(length, movements, Duration::from_millis(delay_ms)) {
+        println!("Unable to draw rope: {}", e);
+    }
+}
+
+// Saves the cells visited by the tail as a PBM image
+pub fn image(length: usize, use_real: bool, path: &str) {
+    println!("=== DAY 9, IMAGE ===");
+    if length == 0 {
+        return println!("A rope needs at least one knot to draw\n");
+    }
+    let data = input::day_input::<Movement<2>>(9);
+    let movements = if use_real { &data.real } else { &data.test };
+
+    let mut rope = Rope::new(length);
+    rope.apply_movements(movements);
+    match fs::write(path, rope.visited_image()) {
+        Ok(_) => println!(
+            "Wrote {} visited cells to {}\n",
+            rope.tail_visits().len(),
+            path
+        ),
+        Err(e) => println!("Unable to write image: {}\n", e),
+    }
+}